@@ -1,13 +1,19 @@
-module gable;
+package gable{% for name in NAMESPACE %}.{{ name }}{% endfor %};
+{%- set namespace = NAMESPACE | join(sep=".") %}
+{%- for import in imports %}
+{%- if IMPORT_NAMESPACES[import] != namespace %}
+import gable.{% if IMPORT_NAMESPACES[import] %}{{ IMPORT_NAMESPACES[import] }}.{% endif %}{{ import }};
+{%- endif %}
+{%- endfor %}
 
 /**
  * {{CLASS_NAME}}
  */
-enum {{CLASS_NAME}} {
+class {{CLASS_NAME}} {
     {%- for field in info.fields %}
     /**
      * {{field.field_desc}}
      */
-    {{ field.field_name }} = {{ field.field_index }}{% if not loop.last %},{% endif %}
+    public {{ field.field_name }}: {{ field.field_type }} = default;
     {%- endfor %}
 }
\ No newline at end of file
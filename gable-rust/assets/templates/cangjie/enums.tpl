@@ -1,17 +1,13 @@
-module gable;
-
-{%- for import in imports %}
-import {{ import }};
-{%- endfor %}
+package gable{% for name in NAMESPACE %}.{{ name }}{% endfor %};
 
 /**
  * {{CLASS_NAME}}
  */
-class {{CLASS_NAME}} {
+enum {{CLASS_NAME}} {
     {%- for field in info.fields %}
     /**
      * {{field.field_desc}}
      */
-    public {{ field.field_name }}: {{ field.field_type }} = default;
+    {{ field.field_name }} = {{ field.field_index }}{% if not loop.last %},{% endif %}
     {%- endfor %}
 }
\ No newline at end of file
@@ -2,7 +2,7 @@
 
 #include <string>
 #include <vector>
-{% for import in imports %}#include "{{ import }}.h"
+{% for import in imports %}#include "{{ IMPORT_FILES[import] }}.h"
 {% endfor %}
 
 /**
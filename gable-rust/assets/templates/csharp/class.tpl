@@ -1,6 +1,9 @@
 using UnityEngine;
+{%- for name in USINGS %}
+using Gable.{{ name }};
+{%- endfor %}
 
-namespace Gable
+namespace Gable{% for name in NAMESPACE %}.{{ name }}{% endfor %}
 {
     public class {{CLASS_NAME}} 
     {
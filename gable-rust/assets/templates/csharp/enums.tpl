@@ -1,6 +1,6 @@
 using UnityEngine;
 
-namespace Gable
+namespace Gable{% for name in NAMESPACE %}.{{ name }}{% endfor %}
 {
     public enum {{CLASS_NAME}} 
     {
@@ -1,12 +1,18 @@
-package gable
-
+package {% if NAMESPACE %}{{ NAMESPACE | last | lower }}{% else %}gable{% endif %}
+{%- set namespace = NAMESPACE | join(sep=".") %}
+{%- set_global imported = [] %}
 {%- for import in imports %}
-import "{{ import }}"
+{%- set import_namespace = IMPORT_NAMESPACES[import] %}
+{%- set import_path = IMPORT_FILES[import] | split(pat="/") | slice(end=-1) | join(sep="/") %}
+{%- if import_namespace != namespace and import_path not in imported %}
+{%- set_global imported = imported | concat(with=import_path) %}
+import {% if import_namespace %}{{ import_namespace | split(pat=".") | last | lower }}{% else %}gable{% endif %} "gable{% if import_path %}/{{ import_path }}{% endif %}"
+{%- endif %}
 {%- endfor %}
 // {{CLASS_NAME}} 
 type {{CLASS_NAME}} struct {
 	{%- for field in info.fields %}
 	// {{field.field_desc}}
-	{{ field.field_name }} {{ field.field_type }} `json:"{{ field.field_name }}"`
+	{{ field.field_name }} {% if field.field_extend and IMPORT_NAMESPACES[field.field_extend] != namespace %}{% if IMPORT_NAMESPACES[field.field_extend] %}{{ IMPORT_NAMESPACES[field.field_extend] | split(pat=".") | last | lower }}{% else %}gable{% endif %}.{% endif %}{{ field.field_type }} `json:"{{ field.field_name }}"`
 	{%- endfor %}
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-package gable
+package {% if NAMESPACE %}{{ NAMESPACE | last | lower }}{% else %}gable{% endif %}
 
 // {{CLASS_NAME}} 
 type {{CLASS_NAME}} int
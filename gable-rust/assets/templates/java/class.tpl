@@ -1,7 +1,7 @@
-package gable;
+package gable{% for name in NAMESPACE %}.{{ name }}{% endfor %};
 
 {%- for import in imports %}
-import {{ import }};
+import {% if MIRROR_FOLDERS %}gable.{% if IMPORT_NAMESPACES[import] %}{{ IMPORT_NAMESPACES[import] }}.{% endif %}{% endif %}{{ import }};
 {%- endfor %}
 /**
  * {{CLASS_NAME}}
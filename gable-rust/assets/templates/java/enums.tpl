@@ -1,4 +1,4 @@
-package gable;
+package gable{% for name in NAMESPACE %}.{{ name }}{% endfor %};
 
 /**
  * {{CLASS_NAME}}
@@ -1,7 +1,7 @@
 // {{CLASS_NAME}}.js
 
 {%- for import in imports %}
-import { {{ import }} } from './{{ import }}.js';
+import { {{ import }} } from '{{ IMPORT_PREFIX }}{{ IMPORT_FILES[import] }}.js';
 {%- endfor %}
 
 /**
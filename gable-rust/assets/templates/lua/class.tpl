@@ -1,7 +1,7 @@
 -- {{CLASS_NAME}}.lua
 
 {%- for import in imports %}
-local {{ import }} = require("{{ import }}")
+local {{ import }} = require("{{ IMPORT_FILES[import] | replace(from="/", to=".") }}")
 {%- endfor %}
 
 --- {{CLASS_NAME}}
@@ -8,4 +8,8 @@ message {{CLASS_NAME}} {
     {%- for field in info.fields %}
     {%- if field.field_type is starting_with("repeated") %}{{ field.field_type }} {{ field.field_name }} = {{ field.field_index }};{% else %}optional {{ field.field_type }} {{ field.field_name }} = {{ field.field_index }}{{ field.field_extend }};{% endif %}// {{field.field_desc}} 
     {%- endfor %}
+    {%- if reserved %}
+    reserved {% for field in reserved %}{{ field.field_index }}{% if not loop.last %}, {% endif %}{% endfor %};
+    reserved {% for field in reserved %}"{{ field.field_name }}"{% if not loop.last %}, {% endif %}{% endfor %};
+    {%- endif %}
 }
\ No newline at end of file
@@ -8,4 +8,8 @@ message {{CLASS_NAME}} {
     {%- for field in info.fields %}
     {{ field.field_type }} {{ field.field_name }} = {{ field.field_index }};// {{field.field_desc}} 
     {%- endfor %}
+    {%- if reserved %}
+    reserved {% for field in reserved %}{{ field.field_index }}{% if not loop.last %}, {% endif %}{% endfor %};
+    reserved {% for field in reserved %}"{{ field.field_name }}"{% if not loop.last %}, {% endif %}{% endfor %};
+    {%- endif %}
 }
\ No newline at end of file
@@ -2,6 +2,9 @@ syntax = "proto3";
 package Gable;
 
 enum {{CLASS_NAME}} { 
+    {%- if allow_alias %}
+    option allow_alias = true;
+    {%- endif %}
     {%- for field in info.fields %}
     {{ field.field_name }} = {{ field.field_index }};// {{field.field_desc}} 
     {%- endfor %}
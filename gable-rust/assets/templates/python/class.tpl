@@ -1,7 +1,7 @@
 # {{CLASS_NAME}}.py
 
 {%- for import in imports %}
-from {{ import }} import {{ import }}
+from {{ IMPORT_FILES[import] | replace(from="/", to=".") }} import {{ import }}
 {%- endfor %}
 
 class {{CLASS_NAME}}:
@@ -1,7 +1,7 @@
 // {{CLASS_NAME}}.ts
 
 {%- for import in imports %}
-import { {{ import }} } from './{{ import }}';
+import { {{ import }} } from '{{ IMPORT_PREFIX }}{{ IMPORT_FILES[import] }}';
 {%- endfor %}
 
 /**
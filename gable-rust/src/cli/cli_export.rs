@@ -1,15 +1,26 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
 
 use clap::Parser;
+use rayon::prelude::*;
 
 use crate::{
     common::{
-        constant,
-        convert::convert,
+        build_cache, constant,
+        convert::{convert, verify_protobuff},
         generate::generate,
+        output, overlay,
         setting::{self, BuildSetting},
     },
-    gui::datas::{gables, tree_data::TreeData, tree_item::TreeItem},
+    gui::datas::{
+        emsgpack_layout_type::EMsgPackLayoutType,
+        gables,
+        log::{LogRecord, LogTrace},
+        tree_data::TreeData,
+        tree_item::TreeItem,
+    },
 };
 
 #[derive(Parser)]
@@ -24,6 +35,14 @@ pub struct ExportArgs {
     #[clap(short = 't', long = "target")]
     pub target: Option<String>,
 
+    /// 指定覆盖数据（overlays目录下的子目录），覆盖构建设置中的配置
+    #[clap(short = 'o', long = "overlay")]
+    pub overlay: Option<String>,
+
+    /// 指定MessagePack数据布局（maps 或 arrays），覆盖构建设置中的配置
+    #[clap(long = "msgpack-layout")]
+    pub msgpack_layout: Option<String>,
+
     /// 导出数据
     #[clap(long = "data")]
     pub data: bool,
@@ -31,6 +50,18 @@ pub struct ExportArgs {
     /// 生成脚本
     #[clap(long = "script")]
     pub script: bool,
+
+    /// 使用生成的proto文件校验导出的protobuff二进制数据
+    #[clap(long = "verify")]
+    pub verify: bool,
+
+    /// 忽略构建缓存，重新导出和生成所有表
+    #[clap(long = "force")]
+    pub force: bool,
+
+    /// 只列出过期的输出文件而不删除，覆盖构建设置中的配置
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
 }
 
 pub fn run_export(args: Vec<String>) -> Result<(), eframe::Error> {
@@ -42,7 +73,7 @@ pub fn run_export(args: Vec<String>) -> Result<(), eframe::Error> {
             return Ok(());
         }
     };
-    if !export_args.data && !export_args.script {
+    if !export_args.data && !export_args.script && !export_args.verify {
         println!(
             "Error: To export data, you need to specify either to export the data or to generate a script."
         );
@@ -55,44 +86,172 @@ pub fn run_export(args: Vec<String>) -> Result<(), eframe::Error> {
     setting::init();
     gables::refresh_gables();
     if export_args.data {
-        execute_convert_command(&export_args.files, &export_args.target);
+        execute_convert_command(&export_args);
     }
     if export_args.script {
-        execute_script_command(&export_args.files, &export_args.target);
+        execute_script_command(&export_args);
+    }
+    if export_args.verify {
+        execute_verify_command(&export_args);
     }
     println!("export successful");
     Ok(())
 }
 
-fn execute_convert_command(files: &[String], target: &Option<String>) {
-    let build_settings: Vec<BuildSetting> = setting::get_build_settings(target);
-    let items: Vec<TreeItem> = gables::get_item_display_name(files);
-    for setting in build_settings.iter() {
-        for item in items.iter() {
-            let datas: HashMap<String, &TreeData> = item.get_datas();
-            if datas.len() <= 0 {
-                continue;
-            }
-            for (_, data) in datas.iter() {
-                convert::execute(setting, *data)
-            }
-        }
-    }
+fn execute_convert_command(export_args: &ExportArgs) {
+    let build_settings: Vec<BuildSetting> = get_build_settings(export_args);
+    let items: Vec<TreeItem> = gables::get_item_display_name(&export_args.files);
+    let roots = gables::TREE_ITEMS.read().unwrap();
+    overlay::clear();
+    build_cache::load();
+    let logs: Vec<Vec<LogRecord>> = build_settings
+        .par_iter()
+        .map(|setting| {
+            LogTrace::capture(|| {
+                let previous: BTreeSet<String> =
+                    build_cache::get_outputs(build_cache::SCOPE_DATA, setting);
+                let datas: BTreeMap<String, Cow<TreeData>> =
+                    match TreeItem::collect_tables(&roots, &items, setting) {
+                        Some(datas) => datas,
+                        None => return,
+                    };
+                // 数据库文件需要包含所有表，有表删除或改名时也需要重新构建
+                let all_datas: Option<BTreeMap<String, Cow<TreeData>>> = if setting.proto_bundle {
+                    match TreeItem::collect_tables(&roots, &roots, setting) {
+                        Some(all_datas) => Some(all_datas),
+                        None => return,
+                    }
+                } else {
+                    None
+                };
+                let tables_changed: bool = all_datas.as_ref().is_some_and(|all_datas| {
+                    build_cache::tables_changed(build_cache::SCOPE_DATA, setting, all_datas)
+                });
+                let rebuilt: usize = convert::execute_tables(setting, &datas, export_args.force);
+                log::info!(
+                    "Export [{}] finished: {} rebuilt, {} unchanged",
+                    setting.display_name,
+                    rebuilt,
+                    datas.len() - rebuilt
+                );
+                if let Some(all_datas) = all_datas.as_ref()
+                    && (rebuilt > 0 || tables_changed)
+                {
+                    convert::export_bundle(setting, all_datas);
+                }
+                // 完整导出时清理已删除或改名的表之前输出的文件
+                let all_datas: Option<&BTreeMap<String, Cow<TreeData>>> =
+                    export_args.files.is_empty().then_some(&datas);
+                convert::clean_stale(setting, all_datas, &previous);
+                output::report(setting, "Export");
+            })
+            .1
+        })
+        .collect();
+    // 按构建设置的顺序输出日志
+    logs.into_iter().for_each(LogTrace::emit);
+    build_cache::save();
 }
-fn execute_script_command(files: &[String], target: &Option<String>) {
-    let build_settings: Vec<BuildSetting> = setting::get_build_settings(target);
-    let items: Vec<TreeItem> = gables::get_item_display_name(files);
+fn execute_script_command(export_args: &ExportArgs) {
+    let build_settings: Vec<BuildSetting> = get_build_settings(export_args);
+    let items: Vec<TreeItem> = gables::get_item_display_name(&export_args.files);
+    let roots = gables::TREE_ITEMS.read().unwrap();
     generate::clear_templates();
+    overlay::clear();
+    build_cache::load();
+    let logs: Vec<Vec<LogRecord>> = build_settings
+        .par_iter()
+        .map(|setting| {
+            LogTrace::capture(|| {
+                generate::preload_templates(setting);
+                let previous: BTreeSet<String> =
+                    build_cache::get_outputs(build_cache::SCOPE_SCRIPT, setting);
+                let datas: BTreeMap<String, Cow<TreeData>> =
+                    match TreeItem::collect_tables(&roots, &items, setting) {
+                        Some(datas) => datas,
+                        None => return,
+                    };
+                // 数据库文件需要包含所有表，有表删除或改名时也需要重新构建
+                let all_datas: Option<BTreeMap<String, Cow<TreeData>>> = if setting.proto_bundle {
+                    match TreeItem::collect_tables(&roots, &roots, setting) {
+                        Some(all_datas) => Some(all_datas),
+                        None => return,
+                    }
+                } else {
+                    None
+                };
+                let tables_changed: bool = all_datas.as_ref().is_some_and(|all_datas| {
+                    build_cache::tables_changed(build_cache::SCOPE_SCRIPT, setting, all_datas)
+                });
+                let rebuilt: usize = generate::execute_tables(setting, &datas, export_args.force);
+                log::info!(
+                    "Generate [{}] finished: {} rebuilt, {} unchanged",
+                    setting.display_name,
+                    rebuilt,
+                    datas.len() - rebuilt
+                );
+                if let Some(all_datas) = all_datas.as_ref()
+                    && (rebuilt > 0 || tables_changed)
+                {
+                    generate::generate_bundle(setting, all_datas);
+                }
+                if export_args.files.is_empty() {
+                    // 完整构建时清理已删除或改名的表之前输出的文件
+                    build_cache::retain_tables(build_cache::SCOPE_SCRIPT, setting, &datas);
+                }
+                output::clean_stale(build_cache::SCOPE_SCRIPT, setting, &previous);
+                output::report(setting, "Generate");
+            })
+            .1
+        })
+        .collect();
+    // 按构建设置的顺序输出日志
+    logs.into_iter().for_each(LogTrace::emit);
+    build_cache::save();
+}
+fn execute_verify_command(export_args: &ExportArgs) {
+    let build_settings: Vec<BuildSetting> = get_build_settings(export_args);
+    let items: Vec<TreeItem> = gables::get_item_display_name(&export_args.files);
+    let roots = gables::TREE_ITEMS.read().unwrap();
+    overlay::clear();
     for setting in build_settings.iter() {
-        generate::preload_templates(setting);
-        for item in items.iter() {
-            let datas: HashMap<String, &TreeData> = item.get_datas();
-            if datas.len() <= 0 {
-                continue;
-            }
-            for (_, data) in datas.iter() {
-                generate::execute(setting, *data)
+        let datas: BTreeMap<String, Cow<TreeData>> =
+            match TreeItem::collect_tables(&roots, &items, setting) {
+                Some(datas) => datas,
+                None => continue,
+            };
+        verify_protobuff::from_tables(setting, &datas);
+    }
+}
+
+/**
+ * 获取构建设置，命令行中指定的选项会替换构建设置中的配置
+ * @param export_args 命令行参数
+ * @return 构建设置列表
+ */
+fn get_build_settings(export_args: &ExportArgs) -> Vec<BuildSetting> {
+    let mut build_settings: Vec<BuildSetting> = setting::get_build_settings(&export_args.target);
+    if let Some(overlay) = &export_args.overlay {
+        for setting in build_settings.iter_mut() {
+            setting.overlay = overlay.clone();
+        }
+    }
+    if export_args.dry_run {
+        for setting in build_settings.iter_mut() {
+            setting.stale_dry_run = true;
+        }
+    }
+    if let Some(msgpack_layout) = &export_args.msgpack_layout {
+        let layout: Option<&EMsgPackLayoutType> = EMsgPackLayoutType::iter()
+            .find(|item| item.as_str().eq_ignore_ascii_case(msgpack_layout));
+        match layout {
+            Some(layout) => {
+                for setting in build_settings.iter_mut() {
+                    setting.msgpack_layout = *layout;
+                }
             }
+            None => log::error!("Unknown MessagePack layout: {}", msgpack_layout),
         }
     }
+    build_settings
 }
@@ -0,0 +1,513 @@
+use crate::{
+    common::{constant, output, setting, setting::BuildSetting, utils},
+    gui::datas::{gables, table_data::TableData, tree_data::TreeData},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+/**
+ * 数据导出的缓存范围
+ */
+pub const SCOPE_DATA: &str = "data";
+/**
+ * 代码生成的缓存范围
+ */
+pub const SCOPE_SCRIPT: &str = "script";
+
+/**
+ * 当前加载的构建缓存
+ */
+static BUILD_CACHE: LazyLock<Mutex<BuildCache>> =
+    LazyLock::new(|| Mutex::new(BuildCache::default()));
+
+/**
+ * 构建缓存，保存在临时目录下，记录每个表上次构建时的内容哈希和输出的文件
+ */
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildCache {
+    /// key为`缓存范围:构建设置标识:表名`，value为内容哈希
+    pub entries: BTreeMap<String, String>,
+    /// key为`缓存范围:构建设置标识`，value为表名和该表输出的文件
+    pub outputs: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    /// key为`缓存范围:构建设置标识`，value为只列出而没有删除的过期文件
+    pub stale: BTreeMap<String, Vec<String>>,
+}
+
+/**
+ * 获取构建缓存文件路径
+ * @return 构建缓存文件路径
+ */
+pub fn get_cache_path() -> PathBuf {
+    setting::get_temp_path().join(constant::BUILD_CACHE_FILE)
+}
+
+/**
+ * 读取构建缓存，每次批量构建前调用，文件不存在或无法解析时视为空缓存
+ */
+pub fn load() {
+    let path: PathBuf = get_cache_path();
+    let cache: BuildCache = if path.exists() {
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Failed to parse {}: {}", path.display(), e);
+                BuildCache::default()
+            }),
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", path.display(), e);
+                BuildCache::default()
+            }
+        }
+    } else {
+        BuildCache::default()
+    };
+    let mut cache: BuildCache = cache;
+    migrate(&mut cache);
+    *BUILD_CACHE.lock().unwrap() = cache;
+}
+
+/**
+ * 旧版本的构建缓存以构建设置名称为键，迁移到构建设置标识，避免全部重新构建并丢失过期文件的记录
+ * @param cache 构建缓存
+ */
+fn migrate(cache: &mut BuildCache) {
+    for build_setting in setting::clone_build_settings().iter() {
+        if build_setting.id.is_empty() {
+            continue;
+        }
+        for scope in [SCOPE_DATA, SCOPE_SCRIPT] {
+            let old_key: String = format!("{}:{}", scope, build_setting.display_name);
+            let new_key: String = get_setting_key(scope, build_setting);
+            if old_key == new_key || cache.outputs.contains_key(&new_key) {
+                continue;
+            }
+            if let Some(tables) = cache.outputs.remove(&old_key) {
+                cache.outputs.insert(new_key.clone(), tables);
+            }
+            if let Some(stale) = cache.stale.remove(&old_key) {
+                cache.stale.insert(new_key.clone(), stale);
+            }
+            let old_prefix: String = format!("{}:", old_key);
+            let entries: Vec<(String, String)> = cache
+                .entries
+                .iter()
+                .filter(|(key, _)| key.starts_with(&old_prefix))
+                .map(|(key, hash)| (key.clone(), hash.clone()))
+                .collect();
+            for (key, hash) in entries {
+                cache.entries.remove(&key);
+                let table_name: &str = &key[old_prefix.len()..];
+                cache
+                    .entries
+                    .insert(format!("{}:{}", new_key, table_name), hash);
+            }
+        }
+    }
+}
+
+/**
+ * 保存构建缓存，批量构建结束后调用
+ */
+pub fn save() {
+    let path: PathBuf = get_cache_path();
+    let cache = BUILD_CACHE.lock().unwrap();
+    let content: String = serde_json::to_string_pretty(&*cache).expect("JSON serialization failed");
+    if let Err(e) = fs::write(&path, content) {
+        log::error!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+/**
+ * 计算表的内容哈希
+ * 包含处理后的表内容（已应用覆盖数据和字段命名）、关联的枚举表和本地化表、构建设置、模板内容和工具版本
+ * @param build_setting 构建设置
+ * @param tree_data 处理后的树数据
+ * @param table_data 表数据
+ * @param templates 使用的模板内容
+ * @return 内容哈希
+ */
+pub fn table_hash(
+    build_setting: &BuildSetting,
+    tree_data: &TreeData,
+    table_data: &TableData,
+    templates: &[String],
+) -> String {
+    let mut hasher: ContentHasher = ContentHasher::new();
+    hasher.write(constant::GABLE_VERSION.as_bytes());
+    // 只列出过期文件的选项和构建设置的名称、标识不影响输出内容
+    let mut hashed_setting: BuildSetting = build_setting.clone();
+    hashed_setting.stale_dry_run = false;
+    hashed_setting.display_name.clear();
+    hashed_setting.id.clear();
+    hasher.write(&serde_json::to_vec(&hashed_setting).expect("JSON serialization failed"));
+    hasher.write(tree_data.file_name.as_bytes());
+    hasher.write(&serde_json::to_vec(&tree_data.content).expect("JSON serialization failed"));
+    for field in table_data.field_info.fields.iter() {
+        if field.field_link.is_empty() {
+            continue;
+        }
+        hasher.write(field.field_link.as_bytes());
+        let enum_content: Option<Vec<u8>> = gables::get_enum_cells(&field.field_link, |data| {
+            serde_json::to_vec(data).expect("JSON serialization failed")
+        });
+        hasher.write(&enum_content.unwrap_or_default());
+        let loc_content: Option<Vec<u8>> = gables::get_loc_cells(&field.field_link, |data| {
+            serde_json::to_vec(data).expect("JSON serialization failed")
+        });
+        hasher.write(&loc_content.unwrap_or_default());
+        if output::is_mirror_folders(build_setting) {
+            // 生成代码引用其他目录下的表时需要导入对应的命名空间
+            let link_table: &str = field.field_link.rsplit('@').next().unwrap_or_default();
+            let link_folder: Option<String> =
+                gables::get_table_folder(link_table, &tree_data.folder);
+            hasher.write(link_folder.unwrap_or_default().as_bytes());
+        }
+    }
+    for template in templates.iter() {
+        hasher.write(template.as_bytes());
+    }
+    hasher.finish()
+}
+
+/**
+ * 表是否与上次构建时相同，输出目录或上次输出的文件不存在时视为已改变
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @param table_name 表名
+ * @param hash 内容哈希
+ * @return 是否可以跳过
+ */
+pub fn is_unchanged(
+    scope: &str,
+    build_setting: &BuildSetting,
+    table_name: &str,
+    hash: &str,
+) -> bool {
+    let output_path: &Path = if scope == SCOPE_SCRIPT {
+        &build_setting.script_path
+    } else {
+        &build_setting.target_path
+    };
+    if !utils::get_absolute_path(output_path).exists() {
+        return false;
+    }
+    let cache = BUILD_CACHE.lock().unwrap();
+    if cache
+        .entries
+        .get(&get_key(scope, build_setting, table_name))
+        .is_none_or(|cached| cached != hash)
+    {
+        return false;
+    }
+    // 输出的文件被手动删除时需要重新构建
+    cache
+        .outputs
+        .get(&get_setting_key(scope, build_setting))
+        .and_then(|tables| tables.get(table_name))
+        .is_none_or(|outputs| outputs.iter().all(|path| Path::new(path).is_file()))
+}
+
+/**
+ * 记录表本次构建的内容哈希和输出的文件
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @param table_name 表名
+ * @param hash 内容哈希
+ * @param outputs 输出的文件
+ */
+pub fn update(
+    scope: &str,
+    build_setting: &BuildSetting,
+    table_name: &str,
+    hash: String,
+    outputs: Vec<String>,
+) {
+    let mut cache = BUILD_CACHE.lock().unwrap();
+    cache
+        .entries
+        .insert(get_key(scope, build_setting, table_name), hash);
+    cache
+        .outputs
+        .entry(get_setting_key(scope, build_setting))
+        .or_default()
+        .insert(table_name.to_string(), outputs);
+}
+
+/**
+ * 记录不参与内容哈希的输出文件，如汇总所有表的数据库文件
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @param name 输出名称
+ * @param outputs 输出的文件
+ */
+pub fn set_outputs(scope: &str, build_setting: &BuildSetting, name: &str, outputs: Vec<String>) {
+    let mut cache = BUILD_CACHE.lock().unwrap();
+    cache
+        .outputs
+        .entry(get_setting_key(scope, build_setting))
+        .or_default()
+        .insert(name.to_string(), outputs);
+}
+
+/**
+ * 移除表的构建记录，表不再有输出时调用，之前输出的文件会成为过期文件
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @param table_name 表名
+ */
+pub fn forget(scope: &str, build_setting: &BuildSetting, table_name: &str) {
+    let mut cache = BUILD_CACHE.lock().unwrap();
+    cache
+        .entries
+        .remove(&get_key(scope, build_setting, table_name));
+    if let Some(tables) = cache
+        .outputs
+        .get_mut(&get_setting_key(scope, build_setting))
+    {
+        tables.remove(table_name);
+    }
+}
+
+/**
+ * 只保留指定表的构建记录，完整构建后调用，已删除或改名的表之前输出的文件会成为过期文件
+ * 汇总数据库的记录总是保留
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @param datas 当前所有的表，key为输出名称
+ */
+pub fn retain_tables(
+    scope: &str,
+    build_setting: &BuildSetting,
+    datas: &BTreeMap<String, Cow<TreeData>>,
+) {
+    let table_names: BTreeSet<&str> = datas.keys().map(|key| key.as_str()).collect();
+    let mut cache = BUILD_CACHE.lock().unwrap();
+    let prefix: String = format!("{}:", get_setting_key(scope, build_setting));
+    cache
+        .entries
+        .retain(|key, _| match key.strip_prefix(&prefix) {
+            Some(table_name) => table_names.contains(table_name),
+            None => true,
+        });
+    if let Some(tables) = cache
+        .outputs
+        .get_mut(&get_setting_key(scope, build_setting))
+    {
+        tables.retain(|name, _| {
+            name == constant::PROTO_BUNDLE_NAME || table_names.contains(name.as_str())
+        });
+    }
+}
+
+/**
+ * 当前的表与上次构建记录的表是否不同，有表删除、改名或取消选择时汇总的输出也需要重新构建
+ * 需要在构建前调用，构建时会记录本次的表
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @param datas 当前所有的表，key为输出名称
+ * @return 是否不同
+ */
+pub fn tables_changed(
+    scope: &str,
+    build_setting: &BuildSetting,
+    datas: &BTreeMap<String, Cow<TreeData>>,
+) -> bool {
+    let cache = BUILD_CACHE.lock().unwrap();
+    let previous: BTreeSet<&str> = match cache.outputs.get(&get_setting_key(scope, build_setting)) {
+        Some(tables) => tables
+            .keys()
+            .map(|name| name.as_str())
+            .filter(|name| *name != constant::PROTO_BUNDLE_NAME)
+            .collect(),
+        None => BTreeSet::new(),
+    };
+    !previous
+        .iter()
+        .copied()
+        .eq(datas.keys().map(|key| key.as_str()))
+}
+
+/**
+ * 获取构建设置记录的所有输出文件，包含之前只列出而没有删除的过期文件
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @return 输出文件
+ */
+pub fn get_outputs(scope: &str, build_setting: &BuildSetting) -> BTreeSet<String> {
+    let cache = BUILD_CACHE.lock().unwrap();
+    let setting_key: String = get_setting_key(scope, build_setting);
+    let mut outputs: BTreeSet<String> = BTreeSet::new();
+    if let Some(tables) = cache.outputs.get(&setting_key) {
+        outputs.extend(tables.values().flatten().cloned());
+    }
+    if let Some(stale) = cache.stale.get(&setting_key) {
+        outputs.extend(stale.iter().cloned());
+    }
+    outputs
+}
+
+/**
+ * 记录只列出而没有删除的过期文件，下次构建时仍会列出
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @param stale 过期文件
+ */
+pub fn set_stale(scope: &str, build_setting: &BuildSetting, stale: Vec<String>) {
+    let mut cache = BUILD_CACHE.lock().unwrap();
+    let setting_key: String = get_setting_key(scope, build_setting);
+    if stale.is_empty() {
+        cache.stale.remove(&setting_key);
+    } else {
+        cache.stale.insert(setting_key, stale);
+    }
+}
+
+/**
+ * 缓存键
+ */
+fn get_key(scope: &str, build_setting: &BuildSetting, table_name: &str) -> String {
+    format!("{}:{}", get_setting_key(scope, build_setting), table_name)
+}
+
+/**
+ * 构建设置的缓存键，使用构建设置标识，修改显示名字后缓存仍然有效
+ */
+fn get_setting_key(scope: &str, build_setting: &BuildSetting) -> String {
+    if build_setting.id.is_empty() {
+        format!("{}:{}", scope, build_setting.display_name)
+    } else {
+        format!("{}:{}", scope, build_setting.id)
+    }
+}
+
+/**
+ * FNV-1a 64位哈希，结果与平台和Rust版本无关，可以保存到文件中
+ * 每段内容前写入长度，避免相邻内容拼接后产生相同的输入
+ */
+struct ContentHasher(u64);
+
+impl ContentHasher {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    fn new() -> Self {
+        ContentHasher(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in (bytes.len() as u64)
+            .to_le_bytes()
+            .iter()
+            .chain(bytes.iter())
+        {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::datas::{esheet_type::ESheetType, gable_data::GableData};
+
+    #[test]
+    fn missing_output_forces_rebuild() {
+        let target_path: PathBuf =
+            std::env::temp_dir().join(format!("gable-build-cache-{}", std::process::id()));
+        fs::create_dir_all(&target_path).unwrap();
+        let output_file: PathBuf = target_path.join("Item.json");
+        fs::write(&output_file, "[]").unwrap();
+        let build_setting: BuildSetting = BuildSetting {
+            id: "build-cache-test".to_string(),
+            target_path: target_path.clone(),
+            ..BuildSetting::default()
+        };
+
+        update(
+            SCOPE_DATA,
+            &build_setting,
+            "Item",
+            "hash".to_string(),
+            vec![output_file.to_string_lossy().to_string()],
+        );
+        assert!(is_unchanged(SCOPE_DATA, &build_setting, "Item", "hash"));
+        assert!(!is_unchanged(SCOPE_DATA, &build_setting, "Item", "other"));
+
+        fs::remove_file(&output_file).unwrap();
+        assert!(!is_unchanged(SCOPE_DATA, &build_setting, "Item", "hash"));
+
+        forget(SCOPE_DATA, &build_setting, "Item");
+        let _ = fs::remove_dir_all(&target_path);
+    }
+
+    #[test]
+    fn table_set_changes_are_detected() {
+        let build_setting: BuildSetting = BuildSetting {
+            id: "tables-changed-test".to_string(),
+            ..BuildSetting::default()
+        };
+        let to_datas = |names: &[&str]| -> BTreeMap<String, Cow<TreeData>> {
+            names
+                .iter()
+                .map(|name| {
+                    (
+                        name.to_string(),
+                        Cow::Owned(TreeData {
+                            gable_type: ESheetType::Normal,
+                            file_name: name.to_string(),
+                            folder: String::new(),
+                            content: GableData {
+                                heads: Vec::new(),
+                                cells: Vec::new(),
+                            },
+                        }),
+                    )
+                })
+                .collect()
+        };
+        for name in ["Item", "Player"] {
+            update(
+                SCOPE_DATA,
+                &build_setting,
+                name,
+                "hash".to_string(),
+                Vec::new(),
+            );
+        }
+        set_outputs(
+            SCOPE_DATA,
+            &build_setting,
+            constant::PROTO_BUNDLE_NAME,
+            Vec::new(),
+        );
+        assert!(!tables_changed(
+            SCOPE_DATA,
+            &build_setting,
+            &to_datas(&["Item", "Player"])
+        ));
+        assert!(tables_changed(
+            SCOPE_DATA,
+            &build_setting,
+            &to_datas(&["Item"])
+        ));
+        assert!(tables_changed(
+            SCOPE_DATA,
+            &build_setting,
+            &to_datas(&["Item", "Player", "Skill"])
+        ));
+        for name in ["Item", "Player"] {
+            forget(SCOPE_DATA, &build_setting, name);
+        }
+    }
+}
@@ -22,10 +22,38 @@ pub const DIR_DATA: &str = "__Datas";
  * 日志目录
  */
 pub const DIR_LOG: &str = "__Temps/__Logs";
+/**
+ * 覆盖数据目录
+ */
+pub const DIR_OVERLAYS: &str = "overlays";
+/**
+ * Protobuff字段编号表目录
+ */
+pub const DIR_PROTO_REGISTRY: &str = "__Protos";
+/**
+ * 汇总所有表的Protobuff数据库消息名称
+ */
+pub const PROTO_BUNDLE_NAME: &str = "ConfigDatabase";
+/**
+ * SQLite数据库文件名
+ */
+pub const SQLITE_DATABASE_NAME: &str = "ConfigDatabase.db";
+/**
+ * 构建缓存文件名，位于临时目录下
+ */
+pub const BUILD_CACHE_FILE: &str = "build_cache.json";
 /**
  * 忽略的目录
  */
-pub const IGNORED_DIRS: &[&str] = &[".vscode", ".git", "_log", DIR_TEMP, DIR_DATA];
+pub const IGNORED_DIRS: &[&str] = &[
+    ".vscode",
+    ".git",
+    "_log",
+    DIR_TEMP,
+    DIR_DATA,
+    DIR_OVERLAYS,
+    DIR_PROTO_REGISTRY,
+];
 /**
  * 设置文件名
 */
@@ -34,6 +62,10 @@ pub const SETTING_PREFS: &str = "appPrefs.json";
  * EXCEL文件扩展名
 */
 pub const EXCEL_EXTENSION: &str = ".xlsx";
+/**
+ * Sheet名称中声明逻辑表名的分隔符，如`Item#Weapon`属于逻辑表`Item`
+*/
+pub const TABLE_NAME_SEPARATOR: &str = "#";
 /**
  * 日期格时间格式
 */
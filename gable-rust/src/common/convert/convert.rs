@@ -1,74 +1,312 @@
 use crate::{
     common::{
-        convert::{convert_csv, convert_json, convert_protobuff, convert_xml, convert_yaml},
+        build_cache, constant,
+        convert::{
+            convert_csv, convert_custom, convert_json, convert_json5, convert_lua, convert_msgpack,
+            convert_protobuff, convert_sql, convert_sqlite, convert_toml, convert_xml,
+            convert_yaml,
+        },
+        naming, output, overlay,
         setting::{self, BuildSetting},
+        utils,
     },
-    gui::datas::{etarget_type::ETargetType, gables, tree_data::TreeData, tree_item::TreeItem},
+    gui::datas::{
+        etarget_type::ETargetType,
+        gables,
+        log::{LogRecord, LogTrace},
+        table_data::TableData,
+        tree_data::TreeData,
+        tree_item::TreeItem,
+    },
+};
+use rayon::prelude::*;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    slice,
 };
-use std::collections::HashMap;
 
 /**
- * 批量转换（所有平台 & 所有表单）
+ * 批量转换（所有平台 & 所有表单），各平台并行导出
+ * @param force 是否忽略构建缓存，重新导出所有表
  */
-pub fn from_all() {
+pub fn from_all(force: bool) {
     let settings = setting::APP_SETTINGS.read().unwrap();
-    for setting in settings.build_settings.iter() {
-        from_target(setting);
-    }
+    overlay::clear();
+    build_cache::load();
+    let logs: Vec<Vec<LogRecord>> = settings
+        .build_settings
+        .par_iter()
+        .map(|setting| LogTrace::capture(|| export_target(setting, force)).1)
+        .collect();
+    // 按构建设置的顺序输出日志
+    logs.into_iter().for_each(LogTrace::emit);
+    build_cache::save();
+}
+
+/**
+ * 批量转换（指定平台 & 所有表单），内容没有变化的表会跳过
+ * @param setting 指定的平台
+ * @param force 是否忽略构建缓存，重新导出所有表
+ */
+pub fn from_target(setting: &BuildSetting, force: bool) {
+    overlay::clear();
+    build_cache::load();
+    export_target(setting, force);
+    build_cache::save();
 }
 
 /**
- * 批量转换（指定平台 & 所有表单）
+ * 导出指定平台的所有表，调用前后需要读取和保存构建缓存
  * @param setting 指定的平台
+ * @param force 是否忽略构建缓存，重新导出所有表
  */
-pub fn from_target(setting: &BuildSetting) {
+fn export_target(setting: &BuildSetting, force: bool) {
     let items = gables::TREE_ITEMS.read().unwrap();
-    let mut datas: HashMap<String, &TreeData> = HashMap::new();
-    for item in items.iter() {
-        let item_datas: HashMap<String, &TreeData> = item.get_datas();
-        if item_datas.len() > 0 {
-            datas.extend(item_datas);
-        }
-    }
+    let datas: BTreeMap<String, Cow<TreeData>> =
+        match TreeItem::collect_tables(&items, &items, setting) {
+            Some(datas) => datas,
+            None => return,
+        };
     if datas.len() <= 0 {
         log::error!("No configurations found to export");
         return;
     }
-    for (_, data) in datas.iter() {
-        execute(setting, *data)
+    let previous: BTreeSet<String> = build_cache::get_outputs(build_cache::SCOPE_DATA, setting);
+    let tables_changed: bool =
+        build_cache::tables_changed(build_cache::SCOPE_DATA, setting, &datas);
+    let rebuilt: usize = execute_tables(setting, &datas, force);
+    if rebuilt > 0 || tables_changed {
+        export_bundle(setting, &datas);
     }
+    clean_stale(setting, Some(&datas), &previous);
+    log::info!(
+        "Export [{}] finished: {} rebuilt, {} unchanged",
+        setting.display_name,
+        rebuilt,
+        datas.len() - rebuilt
+    );
+    output::report(setting, "Export");
 }
 
 /**
- * 批量转换（所有平台 & 指定表单）
+ * 批量转换（所有平台 & 指定表单），指定的表总是重新导出，各平台并行导出
  * @param item 指定表单
 */
 pub fn from_items(item: &TreeItem) {
-    let datas: HashMap<String, &TreeData> = item.get_datas();
-    if datas.len() <= 0 {
+    if item.get_datas().len() <= 0 {
         log::error!("No data found: {}", item.display_name);
         return;
     }
 
+    overlay::clear();
+    build_cache::load();
+    let items = gables::TREE_ITEMS.read().unwrap();
     let settings = setting::APP_SETTINGS.read().unwrap();
-    for setting in settings.build_settings.iter() {
-        for (_, data) in datas.iter() {
-            execute(setting, *data)
+    let logs: Vec<Vec<LogRecord>> = settings
+        .build_settings
+        .par_iter()
+        .map(|setting| {
+            LogTrace::capture(|| {
+                let previous: BTreeSet<String> =
+                    build_cache::get_outputs(build_cache::SCOPE_DATA, setting);
+                let datas: BTreeMap<String, Cow<TreeData>> =
+                    match TreeItem::collect_tables(&items, slice::from_ref(item), setting) {
+                        Some(datas) => datas,
+                        None => return,
+                    };
+                // 数据库文件需要包含所有表
+                let all_datas: Option<BTreeMap<String, Cow<TreeData>>> = if setting.proto_bundle {
+                    match TreeItem::collect_tables(&items, &items, setting) {
+                        Some(all_datas) => Some(all_datas),
+                        None => return,
+                    }
+                } else {
+                    None
+                };
+                let tables_changed: bool = all_datas.as_ref().is_some_and(|all_datas| {
+                    build_cache::tables_changed(build_cache::SCOPE_DATA, setting, all_datas)
+                });
+                let rebuilt: usize = execute_tables(setting, &datas, true);
+                if let Some(all_datas) = all_datas.as_ref()
+                    && (rebuilt > 0 || tables_changed)
+                {
+                    export_bundle(setting, all_datas);
+                }
+                clean_stale(setting, None, &previous);
+                output::report(setting, "Export");
+            })
+            .1
+        })
+        .collect();
+    logs.into_iter().for_each(LogTrace::emit);
+    build_cache::save();
+}
+
+/**
+ * 导出汇总所有表的数据库文件，并记录输出的文件
+ * @param setting 构建设置
+ * @param all_datas 所有的表
+ */
+pub fn export_bundle(setting: &BuildSetting, all_datas: &BTreeMap<String, Cow<TreeData>>) {
+    let (_, outputs) = output::capture(|| convert_protobuff::to_bundle(setting, all_datas));
+    build_cache::set_outputs(
+        build_cache::SCOPE_DATA,
+        setting,
+        constant::PROTO_BUNDLE_NAME,
+        outputs,
+    );
+}
+
+/**
+ * 清理过期的输出文件
+ * 完整导出时传入所有的表，已删除或改名的表之前输出的文件和SQLite数据表也会被清理
+ * @param setting 构建设置
+ * @param all_datas 所有的表，只导出部分表时为None
+ * @param previous 导出前记录的输出文件
+ */
+pub fn clean_stale(
+    setting: &BuildSetting,
+    all_datas: Option<&BTreeMap<String, Cow<TreeData>>>,
+    previous: &BTreeSet<String>,
+) {
+    if let Some(all_datas) = all_datas {
+        build_cache::retain_tables(build_cache::SCOPE_DATA, setting, all_datas);
+        if setting.target_type == ETargetType::SQLite {
+            let table_names: BTreeSet<&str> = all_datas
+                .values()
+                .map(|data| data.file_name.as_str())
+                .collect();
+            convert_sqlite::clean_stale(setting, &table_names);
+        }
+    }
+    output::clean_stale(build_cache::SCOPE_DATA, setting, previous);
+}
+
+/**
+ * 并行转换多个表，日志按表名顺序输出，与逐个转换时相同
+ * SQLite的所有表写入同一个数据库文件，按表名顺序逐个转换，保证相同的输入得到相同的数据库文件
+ * @param build_setting 构建设置
+ * @param datas 表名和表数据的映射
+ * @param force 是否忽略构建缓存
+ * @return 导出的表数量
+ */
+pub fn execute_tables(
+    build_setting: &BuildSetting,
+    datas: &BTreeMap<String, Cow<TreeData>>,
+    force: bool,
+) -> usize {
+    let datas: Vec<&TreeData> = datas.values().map(|data| data.as_ref()).collect();
+    let results: Vec<(bool, Vec<LogRecord>)> = if build_setting.target_type == ETargetType::SQLite {
+        datas
+            .iter()
+            .map(|data| LogTrace::capture(|| execute(build_setting, data, force)))
+            .collect()
+    } else {
+        datas
+            .par_iter()
+            .map(|data| LogTrace::capture(|| execute(build_setting, data, force)))
+            .collect()
+    };
+    let mut rebuilt: usize = 0;
+    for (is_rebuilt, records) in results {
+        LogTrace::emit(records);
+        if is_rebuilt {
+            rebuilt += 1;
         }
     }
+    rebuilt
 }
 
 /**
- * 执行转换
+ * 执行转换，按构建设置处理后的表数据只构建一次，交给对应格式的转换
+ * 内容哈希与构建缓存中的记录相同时跳过，调用前后需要读取和保存构建缓存；可以在多个线程中同时调用
  * @param build_setting 构建设置
  * @param data 树数据
+ * @param force 是否忽略构建缓存
+ * @return 是否导出了该表
  */
-pub fn execute(build_setting: &BuildSetting, data: &TreeData) {
-    match build_setting.target_type {
-        ETargetType::Json => convert_json::to(build_setting, data),
-        ETargetType::CSV => convert_csv::to(build_setting, data),
-        ETargetType::Xml => convert_xml::to(build_setting, data),
-        ETargetType::Yaml => convert_yaml::to(build_setting, data),
-        ETargetType::Protobuff => convert_protobuff::to(build_setting, data),
+pub fn execute(build_setting: &BuildSetting, data: &TreeData, force: bool) -> bool {
+    let source: &TreeData = data;
+    let data: Cow<TreeData> = match transform(build_setting, source) {
+        Some(data) => data,
+        None => return false,
+    };
+    let table_key: String = output::get_table_key(build_setting, &data.folder, &data.file_name);
+    let table_data: TableData = match TableData::new(&data, &build_setting.keyword) {
+        Some(table_data) => table_data,
+        None => {
+            log::debug!("No data to export: {}", data.file_name);
+            build_cache::forget(build_cache::SCOPE_DATA, build_setting, &table_key);
+            return false;
+        }
+    };
+    let table_data: &TableData = &table_data;
+    let templates: Vec<String> = if build_setting.target_type == ETargetType::Custom {
+        let template_path: PathBuf = utils::get_absolute_path(&build_setting.data_template);
+        vec![fs::read_to_string(&template_path).unwrap_or_default()]
+    } else {
+        Vec::new()
+    };
+    let hash: String = build_cache::table_hash(build_setting, &data, table_data, &templates);
+    if !force
+        && build_cache::is_unchanged(build_cache::SCOPE_DATA, build_setting, &table_key, &hash)
+    {
+        log::debug!("Skipped unchanged table: {}", table_key);
+        return false;
+    }
+    let (_, outputs) = output::capture(|| match build_setting.target_type {
+        ETargetType::Json => convert_json::to(build_setting, table_data),
+        ETargetType::CSV => convert_csv::to(build_setting, table_data),
+        ETargetType::Xml => convert_xml::to(build_setting, table_data),
+        ETargetType::Yaml => convert_yaml::to(build_setting, table_data),
+        ETargetType::Protobuff => convert_protobuff::to(build_setting, source, table_data),
+        ETargetType::MessagePack => convert_msgpack::to(build_setting, table_data),
+        ETargetType::SQLite => convert_sqlite::to(build_setting, table_data),
+        ETargetType::Sql => convert_sql::to(build_setting, table_data),
+        ETargetType::Lua => convert_lua::to(build_setting, table_data),
+        ETargetType::Toml => convert_toml::to(build_setting, table_data),
+        ETargetType::Json5 => convert_json5::to(build_setting, table_data),
+        ETargetType::Custom => convert_custom::to(build_setting, table_data),
+    });
+    build_cache::update(
+        build_cache::SCOPE_DATA,
+        build_setting,
+        &table_key,
+        hash,
+        outputs,
+    );
+    true
+}
+
+/**
+ * 按构建设置处理表数据（覆盖数据、字段命名），导出数据和生成代码前调用
+ * @param build_setting 构建设置
+ * @param data 树数据
+ * @return 处理后的树数据，覆盖表重复、覆盖表缺少主键或字段重命名后重名时返回None，该表不能导出
+ */
+pub fn transform<'a>(
+    build_setting: &BuildSetting,
+    data: &'a TreeData,
+) -> Option<Cow<'a, TreeData>> {
+    let mut data: Cow<'a, TreeData> = Cow::Borrowed(data);
+    match overlay::apply(build_setting, &data) {
+        Ok(Some(patched)) => data = Cow::Owned(patched),
+        Ok(None) => {}
+        Err(e) => {
+            log::error!("Export [{}] failed: {}", build_setting.display_name, e);
+            return None;
+        }
+    }
+    match naming::apply(build_setting, &data) {
+        Ok(Some(renamed)) => data = Cow::Owned(renamed),
+        Ok(None) => {}
+        Err(e) => {
+            log::error!("Export [{}] failed: {}", build_setting.display_name, e);
+            return None;
+        }
     }
+    Some(data)
 }
@@ -1,53 +1,73 @@
 use crate::{
-    common::{constant, setting::BuildSetting, utils},
-    gui::datas::{cell_data::CellData, esheet_type::ESheetType, tree_data::TreeData},
-};
-use std::{
-    fs::File,
-    io::{BufWriter, Error, Write},
-    path::PathBuf,
+    common::{output, setting::BuildSetting},
+    gui::datas::{
+        ecsv_array_type::ECsvArrayType,
+        esheet_type::ESheetType,
+        table_data::{self, TableData},
+        tree_data::FieldItem,
+    },
 };
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+/**
+ * KV表的表头，统一使用小写
+ */
+const KV_HEADS: [&str; 3] = ["key", KV_HEAD_TYPE, "value"];
+
+/**
+ * KV表的类型列表头
+ */
+const KV_HEAD_TYPE: &str = "type";
+
+/**
+ * UTF-8 BOM
+ */
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
 
 /**
  * csv 转换
  * @param build_setting 构建设置
- * @param tree_data 树数据
+ * @param table_data 表数据
  * */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    if tree_data.gable_type == ESheetType::Enum {
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
         // 枚举不导出
         return;
     }
 
-    let target_path: PathBuf = utils::get_absolute_path(&build_setting.target_path)
-        .join(format!("{}.csv", tree_data.file_name));
-    let csv_data: Vec<Vec<String>> = to_csv_data(tree_data, &build_setting.keyword);
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.csv", table_data.file_name));
+    let csv_data: Vec<Vec<String>> = to_csv_data(build_setting, table_data);
     if csv_data.is_empty() {
         log::debug!("No data to export [{}]", build_setting.display_name);
+        output::remove(build_setting, &target_path);
         return;
     }
-    // 创建CSV文件
-    let file: Result<File, Error> = File::create(&target_path);
-    if file.is_err() {
-        log::error!(
-            "Export [{}] failed: {}",
-            build_setting.display_name,
-            target_path.to_str().unwrap()
-        );
-        return;
+    let mut contents: Vec<u8> = Vec::new();
+    if build_setting.csv_bom {
+        contents.extend_from_slice(UTF8_BOM);
     }
-    let file = file.unwrap();
-    let mut writer: BufWriter<File> = BufWriter::new(file);
     // 写入CSV数据
+    let delimiter: char = build_setting.csv_delimiter.as_char();
+    let line_ending: &str = build_setting.csv_line_ending.as_line();
     for row_data in csv_data.iter() {
         let mut line: String = String::new();
         let mut is_first: bool = true;
         for col_value in row_data.iter() {
             if !is_first {
-                line.push(',');
+                line.push(delimiter);
             }
-            // 转义包含逗号或引号的值
-            if col_value.contains(',') || col_value.contains('"') || col_value.contains('\n') {
+            // 转义包含分隔符、引号或换行的值
+            if col_value.contains(delimiter)
+                || col_value.contains('"')
+                || col_value.contains('\n')
+                || col_value.contains('\r')
+            {
                 line.push('"');
                 line.push_str(&col_value.replace("\"", "\"\""));
                 line.push('"');
@@ -57,19 +77,12 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
             is_first = false;
         }
 
-        line.push('\n');
-        if let Err(e) = writer.write_all(line.as_bytes()) {
-            log::error!("Error writing [{}] file: {}", build_setting.display_name, e);
-            return;
-        }
+        line.push_str(line_ending);
+        contents.extend_from_slice(line.as_bytes());
     }
 
-    if let Err(e) = writer.flush() {
-        log::error!(
-            "Error flushing [{}] file: {}",
-            build_setting.display_name,
-            e
-        );
+    if let Err(e) = output::write(build_setting, &target_path, contents) {
+        log::error!("Error writing [{}] file: {}", build_setting.display_name, e);
         return;
     }
 
@@ -82,14 +95,13 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
 
 /**
  *  转换为csv数据
- *  @param tree_data 树数据
- *  @param keyword 关键字
+ *  @param build_setting 构建设置
+ *  @param table_data 表数据
  * */
-fn to_csv_data(tree_data: &TreeData, keyword: &str) -> Vec<Vec<String>> {
-    match tree_data.gable_type {
-        ESheetType::Normal => normal_csv_data(tree_data, keyword),
-        ESheetType::KV => kv_csv_data(tree_data, keyword),
-        ESheetType::Localize => localize_csv_data(tree_data, keyword),
+fn to_csv_data(build_setting: &BuildSetting, table_data: &TableData) -> Vec<Vec<String>> {
+    match table_data.gable_type {
+        ESheetType::Normal | ESheetType::Localize => normal_csv_data(build_setting, table_data),
+        ESheetType::KV => kv_csv_data(build_setting, table_data),
         _ => {
             log::error!("The enumeration table does not export as CSV.");
             Vec::new()
@@ -98,281 +110,85 @@ fn to_csv_data(tree_data: &TreeData, keyword: &str) -> Vec<Vec<String>> {
 }
 
 /**
- * 普通表格转换
- * @param tree_data 树数据
- * @param keyword 关键字
+ * 普通表格和本地化表格转换，表头依次为描述行（可选）、字段名行和类型行（可选）
+ * @param build_setting 构建设置
+ * @param table_data 表数据
 */
-fn normal_csv_data(tree_data: &TreeData, keyword: &str) -> Vec<Vec<String>> {
-    let (valids_main, valids) = tree_data.content.get_valid_normal_heads(keyword);
-    if valids_main.is_empty() || valids.is_empty() {
-        return Vec::new();
-    };
+fn normal_csv_data(build_setting: &BuildSetting, table_data: &TableData) -> Vec<Vec<String>> {
+    let fields: &[FieldItem] = &table_data.field_info.fields;
     let mut items: Vec<Vec<String>> = Vec::new();
-
-    let mut desc_row_item: Vec<String> = Vec::new();
-    let mut field_row_item: Vec<String> = Vec::new();
-    let mut type_row_item: Vec<String> = Vec::new();
-    // 主键表头
-    for (_, col_data) in valids_main.iter() {
-        let desc_cell: &&CellData = col_data.get(&constant::TABLE_NORMAL_ROW_DESC).unwrap();
-        let field_cell: &CellData =
-            if let Some(field_cell) = col_data.get(&constant::TABLE_NORMAL_ROW_FIELD) {
-                field_cell
-            } else {
-                return items;
-            };
-        if field_cell.value.is_empty() {
-            return items;
-        };
-        let type_cell: &CellData =
-            if let Some(type_cell) = col_data.get(&constant::TABLE_NORMAL_ROW_TYPE) {
-                type_cell
-            } else {
-                return items;
-            };
-        if type_cell.value.is_empty() {
-            return items;
-        };
-        desc_row_item.push(desc_cell.value.clone());
-        let field_value: String = field_cell.value.replace("*", "");
-        field_row_item.push(field_value);
-        type_row_item.push(type_cell.value.clone());
+    if build_setting.csv_desc_row {
+        items.push(
+            fields
+                .iter()
+                .map(|field| field.field_desc.clone())
+                .collect(),
+        );
     }
-    // 表头
-    for (_, col_data) in valids.iter() {
-        let desc_cell = col_data.get(&constant::TABLE_NORMAL_ROW_DESC).unwrap();
-        let field_cell: &&CellData = col_data.get(&constant::TABLE_NORMAL_ROW_FIELD).unwrap();
-        let type_cell: &&CellData = col_data.get(&constant::TABLE_NORMAL_ROW_TYPE).unwrap();
-        desc_row_item.push(desc_cell.value.clone());
-        field_row_item.push(field_cell.value.clone());
-        type_row_item.push(type_cell.value.clone());
+    items.push(
+        fields
+            .iter()
+            .map(|field| field.field_name.clone())
+            .collect(),
+    );
+    if build_setting.csv_type_row {
+        items.push(
+            fields
+                .iter()
+                .map(|field| field.field_type.as_str().to_string())
+                .collect(),
+        );
     }
-    items.push(desc_row_item);
-    items.push(field_row_item);
-    items.push(type_row_item);
-
-    let max_row = tree_data.content.get_max_row() + 1;
-    for row_index in constant::TABLE_NORMAL_ROW_TOTAL..=max_row {
-        let real_index = row_index - constant::TABLE_NORMAL_ROW_TOTAL;
-        let row_data = if let Some(row_data) = tree_data.content.cells.get(real_index) {
-            row_data
-        } else {
-            continue;
-        };
-        let mut row_valid: bool = true;
-        let mut item_data: Vec<String> = Vec::new();
-        // 检测行数据是否有效，主键没有数据，行数据无效则跳过
-        for (col_index, _) in valids_main.iter() {
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                row_valid = false;
-                continue;
-            };
-            if value_cell.value.is_empty() {
-                row_valid = false;
-                continue;
-            };
-            item_data.push(value_cell.value.clone());
-        }
-        // 行数据无效
-        if !row_valid {
-            continue;
-        }
-
-        for (col_index, _) in valids.iter() {
-            let value_cell = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell.value.clone()
-            } else {
-                String::new()
-            };
-            item_data.push(value_cell);
-        }
-        if item_data.is_empty() {
-            continue;
-        }
-        items.push(item_data);
+    for row_data in table_data.rows.iter() {
+        items.push(
+            fields
+                .iter()
+                .map(|field| to_cell(row_data.get(&field.field_name), build_setting.csv_array))
+                .collect(),
+        );
     }
-    return items;
+    items
 }
 
 /**
- * KV表格转换
- * @param tree_data 树数据
- * @param keyword 关键字
+ * KV表格转换，每个键一行：键、类型（可选）、值
+ * @param build_setting 构建设置
+ * @param table_data 表数据
 */
-fn kv_csv_data(tree_data: &TreeData, keyword: &str) -> Vec<Vec<String>> {
-    let mut items: Vec<Vec<String>> = Vec::new();
-    for row_data in tree_data.content.heads.iter() {
-        let mut head_item: Vec<String> = Vec::new();
-        let field_value: String =
-            if let Some(field_cell) = row_data.get(constant::TABLE_KV_COL_FIELD) {
-                field_cell.value.clone()
-            } else {
-                String::new()
-            };
-        let type_value: String = if let Some(type_cell) = row_data.get(constant::TABLE_KV_COL_TYPE)
-        {
-            type_cell.value.clone()
-        } else {
-            String::new()
-        };
-        let value_value: String =
-            if let Some(value_cell) = row_data.get(constant::TABLE_KV_COL_VALUE) {
-                value_cell.value.clone()
-            } else {
-                String::new()
-            };
-        head_item.push(field_value);
-        head_item.push(type_value);
-        head_item.push(value_value);
-        items.push(head_item);
-    }
-    for row_data in tree_data.content.cells.iter() {
-        let mut row_item: Vec<String> = Vec::new();
-        let field_cell: &CellData =
-            if let Some(field_cell) = row_data.get(constant::TABLE_KV_COL_FIELD) {
-                field_cell
-            } else {
-                continue;
-            };
-        let type_cell: &CellData =
-            if let Some(type_cell) = row_data.get(constant::TABLE_KV_COL_TYPE) {
-                type_cell
-            } else {
-                continue;
-            };
-        let keyword_cell: &CellData =
-            if let Some(keyword_cell) = row_data.get(constant::TABLE_KV_COL_KEYWORD) {
-                keyword_cell
-            } else {
-                continue;
-            };
-        let value_cell: &CellData =
-            if let Some(value_cell) = row_data.get(constant::TABLE_KV_COL_VALUE) {
-                value_cell
-            } else {
-                continue;
-            };
-
-        // 验证字段是否合法
-        if !field_cell.verify_lawful() {
-            continue;
-        }
-        // 验证数据类型是否合法
-        if !type_cell.verify_lawful() {
-            continue;
+fn kv_csv_data(build_setting: &BuildSetting, table_data: &TableData) -> Vec<Vec<String>> {
+    let mut items: Vec<Vec<String>> = vec![
+        KV_HEADS
+            .iter()
+            .filter(|head| build_setting.csv_type_row || **head != KV_HEAD_TYPE)
+            .map(|head| head.to_string())
+            .collect(),
+    ];
+    let row_data: Option<&Map<String, Value>> = table_data.rows.first();
+    for field in table_data.field_info.fields.iter() {
+        let mut item: Vec<String> = vec![field.field_name.clone()];
+        if build_setting.csv_type_row {
+            item.push(field.field_type.as_str().to_string());
         }
-        // 验证keyword是否合法
-        if !keyword_cell.verify_lawful() {
-            continue;
-        }
-        if !keyword_cell.value.contains(keyword) {
-            continue;
-        }
-        row_item.push(field_cell.value.clone());
-        row_item.push(type_cell.value.clone());
-        row_item.push(value_cell.value.clone());
-        items.push(row_item);
+        item.push(to_cell(
+            row_data.and_then(|row_data| row_data.get(&field.field_name)),
+            build_setting.csv_array,
+        ));
+        items.push(item);
     }
-    return items;
+    items
 }
 
 /**
- * 本地化表格转换
- * @param tree_data 树数据
- * @param keyword 关键字
-*/
-fn localize_csv_data(tree_data: &TreeData, keyword: &str) -> Vec<Vec<String>> {
-    let (valids_main, valids) = tree_data.content.get_valid_normal_heads(keyword);
-    if valids_main.is_empty() || valids.is_empty() {
-        return Vec::new();
-    };
-    let mut items: Vec<Vec<String>> = Vec::new();
-
-    let mut desc_row_item: Vec<String> = Vec::new();
-    let mut field_row_item: Vec<String> = Vec::new();
-    let mut type_row_item: Vec<String> = Vec::new();
-    // 主键表头
-    for (_, col_data) in valids_main.iter() {
-        let desc_cell: &&CellData = col_data.get(&constant::TABLE_LOCALIZE_ROW_DESC).unwrap();
-        let field_cell: &CellData =
-            if let Some(field_cell) = col_data.get(&constant::TABLE_LOCALIZE_ROW_FIELD) {
-                field_cell
-            } else {
-                return items;
-            };
-        if field_cell.value.is_empty() {
-            return items;
-        };
-        let type_cell: &CellData =
-            if let Some(type_cell) = col_data.get(&constant::TABLE_LOCALIZE_ROW_TYPE) {
-                type_cell
-            } else {
-                return items;
-            };
-        if type_cell.value.is_empty() {
-            return items;
-        };
-        desc_row_item.push(desc_cell.value.clone());
-        let field_value: String = field_cell.value.replace("*", "");
-        field_row_item.push(field_value);
-        type_row_item.push(type_cell.value.clone());
-    }
-    // 表头
-    for (_, col_data) in valids.iter() {
-        let desc_cell = col_data.get(&constant::TABLE_LOCALIZE_ROW_DESC).unwrap();
-        let field_cell: &&CellData = col_data.get(&constant::TABLE_LOCALIZE_ROW_FIELD).unwrap();
-        let type_cell: &&CellData = col_data.get(&constant::TABLE_LOCALIZE_ROW_TYPE).unwrap();
-        desc_row_item.push(desc_cell.value.clone());
-        field_row_item.push(field_cell.value.clone());
-        type_row_item.push(type_cell.value.clone());
-    }
-    items.push(desc_row_item);
-    items.push(field_row_item);
-    items.push(type_row_item);
-
-    let max_row: usize = tree_data.content.get_max_row() + 1;
-    for row_index in constant::TABLE_LOCALIZE_ROW_TOTAL..=max_row {
-        let real_index = row_index - constant::TABLE_LOCALIZE_ROW_TOTAL;
-        let row_data = if let Some(row_data) = tree_data.content.cells.get(real_index) {
-            row_data
-        } else {
-            continue;
-        };
-        let mut row_valid: bool = true;
-        let mut item_data: Vec<String> = Vec::new();
-        // 检测行数据是否有效，主键没有数据，行数据无效则跳过
-        for (col_index, _) in valids_main.iter() {
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                row_valid = false;
-                continue;
-            };
-            if value_cell.value.is_empty() {
-                row_valid = false;
-                continue;
-            };
-            item_data.push(value_cell.value.clone());
-        }
-        // 行数据无效
-        if !row_valid {
-            continue;
-        }
-
-        for (col_index, _) in valids.iter() {
-            let value_cell = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell.value.clone()
-            } else {
-                String::new()
-            };
-            item_data.push(value_cell);
-        }
-        if item_data.is_empty() {
-            continue;
+ * 单元格文本，缺少的值为空
+ * @param value 值
+ * @param array_type 数组和向量的写法
+ */
+fn to_cell(value: Option<&Value>, array_type: ECsvArrayType) -> String {
+    match value {
+        Some(value @ (Value::Array(_) | Value::Object(_))) if array_type == ECsvArrayType::Json => {
+            serde_json::to_string(value).expect("JSON serialization failed")
         }
-        items.push(item_data);
+        Some(value) => table_data::to_text(value),
+        None => String::new(),
     }
-    return items;
 }
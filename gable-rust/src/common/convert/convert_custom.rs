@@ -0,0 +1,119 @@
+use crate::{
+    common::{output, setting::BuildSetting, utils},
+    gui::datas::table_data::TableData,
+};
+use serde_json::{Value, json};
+use std::{error::Error as StdError, fs, io::Error, path::PathBuf};
+use tera::{Context, Tera};
+
+/**
+ * 使用自定义Tera模板导出数据
+ * 模板中可以使用的变量：
+ * CLASS_NAME 表名；table_type 表类型（Normal、Localize、KV、Enum）；keyword 关键字；
+ * main_fields 主键字段；fields 字段（field_name、field_type、field_desc、field_link、field_index）；
+ * rows 行数据，与json导出的数据一致，枚举表的成员在fields中
+ * 渲染结果为空时不生成文件，模板可以借此跳过不需要的表
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    let template_path: PathBuf = utils::get_absolute_path(&build_setting.data_template);
+    let template: String = match fs::read_to_string(&template_path) {
+        Ok(template) => template,
+        Err(e) => {
+            log::error!(
+                "Export [{}] failed: unable to read data template {} - {}",
+                build_setting.display_name,
+                template_path.to_string_lossy(),
+                e
+            );
+            return;
+        }
+    };
+    let extension: &str = build_setting.data_extension.trim_start_matches('.');
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.{}", table_data.file_name, extension));
+
+    let context: Context = create_context(build_setting, table_data);
+    let rendered: String = match Tera::one_off(&template, &context, false) {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            let mut message: String = e.to_string();
+            let mut source: Option<&dyn StdError> = e.source();
+            while let Some(inner) = source {
+                message.push_str(&format!(": {}", inner));
+                source = inner.source();
+            }
+            log::error!("Template error: {} - {}", table_data.file_name, message);
+            return;
+        }
+    };
+    if rendered.trim().is_empty() {
+        log::debug!("No data to export: {}", target_path.to_str().unwrap());
+        output::remove(build_setting, &target_path);
+        return;
+    }
+
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, rendered);
+    if result.is_err() {
+        log::error!(
+            "Export [{}] failed: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    }
+}
+
+/**
+ * 创建模板上下文
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ * @return 模板上下文
+ */
+fn create_context(build_setting: &BuildSetting, table_data: &TableData) -> Context {
+    let main_fields: Vec<Value> = table_data
+        .field_info
+        .main_fields
+        .iter()
+        .map(|field| {
+            json!({
+                "field_name": field.field_name,
+                "field_type": field.field_type.as_str(),
+            })
+        })
+        .collect();
+    let fields: Vec<Value> = table_data
+        .field_info
+        .fields
+        .iter()
+        .map(|field| {
+            json!({
+                "field_name": field.field_name,
+                "field_type": field.field_type.as_str(),
+                "field_desc": field.field_desc,
+                "field_link": field.field_link,
+                "field_index": field.field_index,
+            })
+        })
+        .collect();
+
+    let mut context: Context = Context::new();
+    context.insert("CLASS_NAME", &table_data.file_name);
+    context.insert("table_type", &format!("{:?}", table_data.gable_type));
+    context.insert("keyword", &build_setting.keyword);
+    context.insert("main_fields", &main_fields);
+    context.insert("fields", &fields);
+    // 枚举表没有行数据，成员在fields中（field_index为枚举值）
+    context.insert("rows", &table_data.rows);
+    context
+}
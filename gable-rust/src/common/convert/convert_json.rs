@@ -1,35 +1,51 @@
 use crate::{
-    common::{setting::BuildSetting, utils},
-    gui::datas::{esheet_type::ESheetType, tree_data::TreeData},
+    common::{convert::convert_json_schema, output, setting::BuildSetting},
+    gui::datas::{
+        ejson_layout_type::EJsonLayoutType, esheet_type::ESheetType, table_data::TableData,
+    },
 };
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 use std::{io::Error, path::PathBuf};
 
 /**
  * 将数据转换为json
  * @param build_setting 构建设置
- * @param tree_data 树数据
+ * @param table_data 表数据
  */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    if tree_data.gable_type == ESheetType::Enum {
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
         // 枚举不导出
         return;
     }
-    let target_path: PathBuf = utils::get_absolute_path(&build_setting.target_path)
-        .join(format!("{}.json", tree_data.file_name));
-    let json_data: Vec<Map<String, Value>> = tree_data.to_values(&build_setting.keyword);
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.json", table_data.file_name));
+    let json_data: &[Map<String, Value>] = &table_data.rows;
     if json_data.is_empty() {
         log::debug!("No data to export: {}", target_path.to_str().unwrap());
+        output::remove(build_setting, &target_path);
         return;
     }
-    let contents: String = if tree_data.gable_type == ESheetType::KV {
-        serde_json::to_string_pretty(&json_data[0]).expect("JSON serialization failed")
+    let value: Value = if table_data.gable_type == ESheetType::KV {
+        Value::Object(json_data[0].clone())
     } else {
-        serde_json::to_string_pretty(&json_data).expect("JSON serialization failed")
+        match build_setting.json_layout {
+            EJsonLayoutType::Array => {
+                Value::Array(json_data.iter().cloned().map(Value::Object).collect())
+            }
+            EJsonLayoutType::Keyed => to_keyed(table_data),
+            EJsonLayoutType::Columns => to_columns(table_data),
+        }
     };
-    // let contents: String =
-    //     serde_json::to_string_pretty(&json_data).expect("JSON serialization failed");
-    let result: Result<(), Error> = std::fs::write(&target_path, contents);
+    let contents: String = if build_setting.json_minify {
+        serde_json::to_string(&value).expect("JSON serialization failed")
+    } else {
+        serde_json::to_string_pretty(&value).expect("JSON serialization failed")
+    };
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, contents);
     if result.is_err() {
         log::error!(
             "Export [{}] failed: {}",
@@ -43,4 +59,102 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
             target_path.to_str().unwrap()
         );
     }
+    if build_setting.json_schema {
+        convert_json_schema::to(build_setting, table_data);
+    }
+}
+
+/**
+ * 以主键为键组织数据，多个主键时逐级嵌套，如`{"1001": {"1": {...}}}`
+ * 表没有主键时退化为数组
+ * @param table_data 表数据
+ * @return json对象
+ */
+fn to_keyed(table_data: &TableData) -> Value {
+    let primary_keys: Vec<&str> = table_data.primary_keys();
+    if primary_keys.is_empty() {
+        log::warn!(
+            "{} has no primary key, exported as an array",
+            table_data.file_name
+        );
+        return Value::Array(table_data.rows.iter().cloned().map(Value::Object).collect());
+    }
+    let mut root: Map<String, Value> = Map::new();
+    for row_data in table_data.rows.iter() {
+        let keys: Vec<String> = primary_keys
+            .iter()
+            .filter_map(|key| row_data.get(*key))
+            .map(|key| match key {
+                Value::String(s) => s.clone(),
+                _ => key.to_string(),
+            })
+            .collect();
+        if keys.len() != primary_keys.len() {
+            continue;
+        }
+        if insert_keyed(&mut root, &keys, row_data.clone()) {
+            log::warn!(
+                "Duplicate primary key {} in {}",
+                keys.join(","),
+                table_data.file_name
+            );
+        }
+    }
+    Value::Object(root)
+}
+
+/**
+ * 按主键逐级插入一行数据
+ * @param table 当前层级的对象
+ * @param keys 剩余的主键值
+ * @param row_data 行数据
+ * @return 是否覆盖了相同主键的行
+ */
+fn insert_keyed(
+    table: &mut Map<String, Value>,
+    keys: &[String],
+    row_data: Map<String, Value>,
+) -> bool {
+    if keys.len() == 1 {
+        return table
+            .insert(keys[0].clone(), Value::Object(row_data))
+            .is_some();
+    }
+    let child: &mut Value = table
+        .entry(keys[0].clone())
+        .or_insert_with(|| Value::Object(Map::new()));
+    match child {
+        Value::Object(child) => insert_keyed(child, &keys[1..], row_data),
+        _ => false,
+    }
+}
+
+/**
+ * 按列组织数据：`{"fields": [...], "rows": [[...]]}`，字段顺序与生成的脚本一致
+ * @param table_data 表数据
+ * @return json对象
+ */
+fn to_columns(table_data: &TableData) -> Value {
+    let fields: Vec<&str> = table_data
+        .field_info
+        .fields
+        .iter()
+        .map(|field| field.field_name.as_str())
+        .collect();
+    let rows: Vec<Value> = table_data
+        .rows
+        .iter()
+        .map(|row_data| {
+            Value::Array(
+                fields
+                    .iter()
+                    .map(|field| row_data.get(*field).cloned().unwrap_or(Value::Null))
+                    .collect(),
+            )
+        })
+        .collect();
+    json!({
+        "fields": fields,
+        "rows": rows,
+    })
 }
@@ -0,0 +1,117 @@
+use crate::{
+    common::{convert::convert_toml, output, setting::BuildSetting},
+    gui::datas::{esheet_type::ESheetType, table_data::TableData},
+};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, io::Error, path::PathBuf};
+
+/**
+ * 将数据转换为json5，字段描述写为注释
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
+        // 枚举不导出
+        return;
+    }
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.json5", table_data.file_name));
+    let values: &[Map<String, Value>] = &table_data.rows;
+    if values.is_empty() {
+        log::debug!("No data to export: {}", target_path.to_str().unwrap());
+        output::remove(build_setting, &target_path);
+        return;
+    }
+    let descs: HashMap<&str, &str> = convert_toml::get_field_descs(table_data);
+
+    let mut contents: String = String::new();
+    if table_data.gable_type == ESheetType::KV {
+        contents.push_str("{\n");
+        for (field_name, value) in values[0].iter() {
+            if let Some(desc) = descs.get(field_name.as_str()) {
+                contents.push_str(&format!("  // {}\n", convert_toml::single_line(desc)));
+            }
+            contents.push_str(&format!("  {}: {},\n", to_key(field_name), to_value(value)));
+        }
+        contents.push_str("}\n");
+    } else {
+        // 字段描述只在文件开头写一次
+        for field in table_data.field_info.fields.iter() {
+            if !field.field_desc.is_empty() {
+                contents.push_str(&format!(
+                    "// {}: {}\n",
+                    field.field_name,
+                    convert_toml::single_line(&field.field_desc)
+                ));
+            }
+        }
+        contents.push_str("[\n");
+        for row_data in values.iter() {
+            contents.push_str(&format!("  {},\n", to_object(row_data)));
+        }
+        contents.push_str("]\n");
+    }
+
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, contents);
+    if result.is_err() {
+        log::error!(
+            "Export [{}] failed: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    }
+}
+
+/**
+ * 键名，合法的标识符不加引号
+ */
+fn to_key(name: &str) -> String {
+    let mut chars = name.chars();
+    let identifier: bool = match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    };
+    if identifier {
+        name.to_string()
+    } else {
+        Value::String(name.to_string()).to_string()
+    }
+}
+
+/**
+ * 对象写在同一行
+ */
+fn to_object(object: &Map<String, Value>) -> String {
+    let items: Vec<String> = object
+        .iter()
+        .map(|(key, item)| format!("{}: {}", to_key(key), to_value(item)))
+        .collect();
+    format!("{{ {} }}", items.join(", "))
+}
+
+/**
+ * 值转换为json5字面量
+ */
+fn to_value(value: &Value) -> String {
+    match value {
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(to_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(object) => to_object(object),
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{
+    common::{output, setting::BuildSetting},
+    gui::datas::{
+        edata_type::EDataType,
+        ejson_layout_type::EJsonLayoutType,
+        esheet_type::ESheetType,
+        gables,
+        table_data::TableData,
+        tree_data::{FieldInfo, FieldItem},
+    },
+};
+use serde_json::{Map, Value, json};
+use std::{io::Error, path::PathBuf};
+
+/**
+ * JSON Schema版本
+ */
+const JSON_SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/**
+ * 生成与json导出数据对应的JSON Schema，文件名为`表名.schema.json`
+ * 数据布局与json导出一致：KV表为对象，普通表按json_layout为数组、按主键组织的对象或按列组织的对象
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
+        // 枚举不导出
+        return;
+    }
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.schema.json", table_data.file_name));
+    let field_info: &FieldInfo = &table_data.field_info;
+
+    let mut schema: Map<String, Value> = Map::new();
+    schema.insert("$schema".to_string(), Value::from(JSON_SCHEMA_DRAFT));
+    schema.insert(
+        "title".to_string(),
+        Value::from(table_data.file_name.clone()),
+    );
+    if table_data.gable_type == ESheetType::KV {
+        // KV表的值为空时不会导出，所以没有必填字段
+        schema.extend(to_object(field_info, &[]));
+    } else {
+        let primary_keys: Vec<&str> = table_data.primary_keys();
+        let row: Map<String, Value> = to_object(field_info, &primary_keys);
+        schema.insert("$defs".to_string(), json!({ "row": row }));
+        let layout: Value = match build_setting.json_layout {
+            EJsonLayoutType::Keyed if !primary_keys.is_empty() => {
+                to_keyed(field_info, &primary_keys)
+            }
+            EJsonLayoutType::Columns => to_columns(field_info, &primary_keys),
+            _ => json!({
+                "type": "array",
+                "items": { "$ref": "#/$defs/row" },
+            }),
+        };
+        if let Value::Object(layout) = layout {
+            schema.extend(layout);
+        }
+    }
+
+    let contents: String =
+        serde_json::to_string_pretty(&Value::Object(schema)).expect("JSON serialization failed");
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, contents);
+    if result.is_err() {
+        log::error!(
+            "Export [{}] failed: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    }
+}
+
+/**
+ * 生成一行数据的对象结构，主键为必填字段
+ * @param field_info 字段信息
+ * @param primary_keys 主键字段名
+ * @return 对象结构
+ */
+fn to_object(field_info: &FieldInfo, primary_keys: &[&str]) -> Map<String, Value> {
+    let mut properties: Map<String, Value> = Map::new();
+    for field in field_info.fields.iter() {
+        properties.insert(field.field_name.clone(), Value::Object(to_field(field)));
+    }
+    let mut object: Map<String, Value> = Map::new();
+    object.insert("type".to_string(), Value::from("object"));
+    object.insert("properties".to_string(), Value::Object(properties));
+    if !primary_keys.is_empty() {
+        object.insert("required".to_string(), Value::from(primary_keys.to_vec()));
+    }
+    object.insert("additionalProperties".to_string(), Value::Bool(false));
+    object
+}
+
+/**
+ * 按主键组织的数据，多个主键时逐级嵌套，json对象的键都是字符串
+ * @param field_info 字段信息
+ * @param primary_keys 主键字段名
+ * @return 对象结构
+ */
+fn to_keyed(field_info: &FieldInfo, primary_keys: &[&str]) -> Value {
+    let mut schema: Value = json!({ "$ref": "#/$defs/row" });
+    for key in primary_keys.iter().rev() {
+        let numeric: bool = field_info
+            .fields
+            .iter()
+            .find(|field| field.field_name == *key)
+            .map(|field| is_integer(&field.field_type))
+            .unwrap_or(false);
+        let mut object: Map<String, Value> = Map::new();
+        object.insert("type".to_string(), Value::from("object"));
+        if numeric {
+            object.insert(
+                "propertyNames".to_string(),
+                json!({ "pattern": "^-?[0-9]+$" }),
+            );
+        }
+        object.insert("additionalProperties".to_string(), schema);
+        schema = Value::Object(object);
+    }
+    schema
+}
+
+/**
+ * 按列组织的数据：`{"fields": [...], "rows": [[...]]}`，行中缺少的值为null
+ * @param field_info 字段信息
+ * @param primary_keys 主键字段名
+ * @return 对象结构
+ */
+fn to_columns(field_info: &FieldInfo, primary_keys: &[&str]) -> Value {
+    let fields: Vec<&str> = field_info
+        .fields
+        .iter()
+        .map(|field| field.field_name.as_str())
+        .collect();
+    let columns: Vec<Value> = field_info
+        .fields
+        .iter()
+        .map(|field| {
+            let mut column: Map<String, Value> = to_field(field);
+            if primary_keys.contains(&field.field_name.as_str()) {
+                return Value::Object(column);
+            }
+            let description: Option<Value> = column.remove("description");
+            let mut nullable: Map<String, Value> = Map::new();
+            if let Some(description) = description {
+                nullable.insert("description".to_string(), description);
+            }
+            nullable.insert("anyOf".to_string(), json!([column, { "type": "null" }]));
+            Value::Object(nullable)
+        })
+        .collect();
+    json!({
+        "type": "object",
+        "properties": {
+            "fields": { "const": fields },
+            "rows": {
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "prefixItems": columns,
+                    "items": false,
+                },
+            },
+        },
+        "required": ["fields", "rows"],
+        "additionalProperties": false,
+    })
+}
+
+/**
+ * 生成字段的结构，描述取自表头的描述行
+ * @param field 字段
+ * @return 字段结构
+ */
+fn to_field(field: &FieldItem) -> Map<String, Value> {
+    let mut schema: Map<String, Value> = match &field.field_type {
+        EDataType::IntArr | EDataType::LongArr => to_array(json!({ "type": "integer" })),
+        EDataType::StringArr => to_array(json!({ "type": "string" })),
+        EDataType::BooleanArr => to_array(json!({ "type": "boolean" })),
+        EDataType::FloatArr => to_array(json!({ "type": "number" })),
+        EDataType::Vector2Arr => to_array(Value::Object(to_vector(&["x", "y"]))),
+        EDataType::Vector3Arr => to_array(Value::Object(to_vector(&["x", "y", "z"]))),
+        EDataType::Vector4Arr => to_array(Value::Object(to_vector(&["x", "y", "z", "w"]))),
+        EDataType::Vector2 => to_vector(&["x", "y"]),
+        EDataType::Vector3 => to_vector(&["x", "y", "z"]),
+        EDataType::Vector4 => to_vector(&["x", "y", "z", "w"]),
+        EDataType::Enum => to_enum(&field.field_link),
+        data_type => {
+            let type_name: &str = match data_type {
+                EDataType::Boolean => "boolean",
+                EDataType::Float
+                | EDataType::Percentage
+                | EDataType::Permillage
+                | EDataType::Permian => "number",
+                _ if is_integer(data_type) => "integer",
+                _ => "string",
+            };
+            let mut schema: Map<String, Value> = Map::new();
+            schema.insert("type".to_string(), Value::from(type_name));
+            schema
+        }
+    };
+    if !field.field_desc.is_empty() {
+        schema.insert(
+            "description".to_string(),
+            Value::from(field.field_desc.clone()),
+        );
+    }
+    schema
+}
+
+/**
+ * 数组结构
+ */
+fn to_array(items: Value) -> Map<String, Value> {
+    let mut schema: Map<String, Value> = Map::new();
+    schema.insert("type".to_string(), Value::from("array"));
+    schema.insert("items".to_string(), items);
+    schema
+}
+
+/**
+ * 向量结构，分量都是数字
+ */
+fn to_vector(components: &[&str]) -> Map<String, Value> {
+    let mut properties: Map<String, Value> = Map::new();
+    for component in components.iter() {
+        properties.insert(component.to_string(), json!({ "type": "number" }));
+    }
+    let mut schema: Map<String, Value> = Map::new();
+    schema.insert("type".to_string(), Value::from("object"));
+    schema.insert("properties".to_string(), Value::Object(properties));
+    schema.insert("required".to_string(), Value::from(components.to_vec()));
+    schema.insert("additionalProperties".to_string(), Value::Bool(false));
+    schema
+}
+
+/**
+ * 枚举结构，取值范围来自关联的枚举表，枚举表不存在时只限定为整数
+ * @param link_name 关联的枚举名
+ * @return 枚举结构
+ */
+fn to_enum(link_name: &str) -> Map<String, Value> {
+    let mut schema: Map<String, Value> = Map::new();
+    schema.insert("type".to_string(), Value::from("integer"));
+    let members: Option<Vec<(i64, String)>> = gables::get_enum_members(link_name);
+    match members {
+        Some(members) if !members.is_empty() => {
+            let values: Vec<i64> = members.iter().map(|(value, _)| *value).collect();
+            let names: Vec<&str> = members.iter().map(|(_, name)| name.as_str()).collect();
+            schema.insert("enum".to_string(), Value::from(values));
+            schema.insert("x-enumNames".to_string(), Value::from(names));
+        }
+        _ => {
+            log::warn!(
+                "Enum {} not found, schema only checks for an integer",
+                link_name
+            );
+        }
+    }
+    schema
+}
+
+/**
+ * 是否导出为整数
+ */
+fn is_integer(data_type: &EDataType) -> bool {
+    matches!(
+        data_type,
+        EDataType::Int | EDataType::Long | EDataType::Time | EDataType::Date | EDataType::Enum
+    )
+}
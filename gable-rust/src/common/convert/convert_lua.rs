@@ -0,0 +1,293 @@
+use crate::{
+    common::{output, setting::BuildSetting},
+    gui::datas::{esheet_type::ESheetType, table_data::TableData},
+};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, io::Error, path::PathBuf};
+
+/**
+ * Lua关键字，不能直接作为表的键
+ */
+const LUA_KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/**
+ * 只读表辅助函数，修改表时抛出错误
+ * 数据保存在代理表的`__index`中，`#`和`pairs`依赖`__len`、`__pairs`元方法，需要Lua 5.2及以上版本，Lua 5.1和LuaJIT不支持
+ */
+const LUA_READONLY_FUNCTION: &str = r#"local function readonly(t)
+    for k, v in pairs(t) do
+        if type(v) == "table" then
+            t[k] = readonly(v)
+        end
+    end
+    return setmetatable({}, {
+        __index = t,
+        __newindex = function()
+            error("attempt to modify a read-only table", 2)
+        end,
+        __len = function()
+            return #t
+        end,
+        __pairs = function()
+            return next, t, nil
+        end,
+    })
+end
+"#;
+
+/**
+ * 按主键分组的Lua表节点
+ */
+enum LuaNode<'a> {
+    /// 行数据
+    Row(&'a Map<String, Value>),
+    /// 下一级主键
+    Table(LuaTable<'a>),
+}
+
+#[derive(Default)]
+struct LuaTable<'a> {
+    /// 键和节点，保持表格中的顺序
+    entries: Vec<(&'a Value, LuaNode<'a>)>,
+    /// 键 -> entries序号
+    index: HashMap<String, usize>,
+}
+
+/**
+ * 将数据转换为Lua源文件，文件内容为`return { ... }`
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
+        // 枚举不导出
+        return;
+    }
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.lua", table_data.file_name));
+    let values: &[Map<String, Value>] = &table_data.rows;
+    if values.is_empty() {
+        log::debug!("No data to export: {}", target_path.to_str().unwrap());
+        output::remove(build_setting, &target_path);
+        return;
+    }
+
+    let mut contents: String = String::new();
+    if build_setting.lua_readonly {
+        contents.push_str(LUA_READONLY_FUNCTION);
+        contents.push('\n');
+        contents.push_str("local data = ");
+    } else {
+        contents.push_str("return ");
+    }
+    if table_data.gable_type == ESheetType::KV {
+        write_row(&mut contents, &values[0]);
+    } else if build_setting.lua_keyed {
+        let primary_keys: Vec<&str> = table_data.primary_keys();
+        let table: LuaTable = group_rows(&table_data.file_name, &primary_keys, values);
+        write_table(&mut contents, &table, 0);
+    } else {
+        contents.push_str("{\n");
+        for row_data in values.iter() {
+            push_indent(&mut contents, 1);
+            write_row(&mut contents, row_data);
+            contents.push_str(",\n");
+        }
+        contents.push('}');
+    }
+    contents.push('\n');
+    if build_setting.lua_readonly {
+        contents.push_str("\nreturn readonly(data)\n");
+    }
+
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, contents);
+    if result.is_err() {
+        log::error!(
+            "Export [{}] failed: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    }
+}
+
+/**
+ * 按主键分组，多个主键时逐级嵌套，如`[1001] = { [1] = {...} }`
+ * @param table_name 表名
+ * @param primary_keys 主键字段名
+ * @param values 行数据
+ * @return 分组后的表
+ */
+fn group_rows<'a>(
+    table_name: &str,
+    primary_keys: &[&str],
+    values: &'a [Map<String, Value>],
+) -> LuaTable<'a> {
+    let mut root: LuaTable = LuaTable::default();
+    for row_data in values.iter() {
+        let keys: Vec<&Value> = primary_keys
+            .iter()
+            .filter_map(|key| row_data.get(*key))
+            .collect();
+        if keys.is_empty() || keys.len() != primary_keys.len() {
+            log::warn!("Row without primary key skipped in {}", table_name);
+            continue;
+        }
+        let mut table: &mut LuaTable = &mut root;
+        for (depth, key) in keys.iter().enumerate() {
+            let index_key: String = key.to_string();
+            let last: bool = depth == keys.len() - 1;
+            let pos: usize = match table.index.get(&index_key) {
+                Some(pos) => {
+                    if last {
+                        log::warn!("Duplicate primary key {} in {}", index_key, table_name);
+                        table.entries[*pos].1 = LuaNode::Row(row_data);
+                    }
+                    *pos
+                }
+                None => {
+                    let node: LuaNode = if last {
+                        LuaNode::Row(row_data)
+                    } else {
+                        LuaNode::Table(LuaTable::default())
+                    };
+                    table.entries.push((*key, node));
+                    table.index.insert(index_key, table.entries.len() - 1);
+                    table.entries.len() - 1
+                }
+            };
+            if last {
+                break;
+            }
+            table = match &mut table.entries[pos].1 {
+                LuaNode::Table(child) => child,
+                LuaNode::Row(_) => break,
+            };
+        }
+    }
+    root
+}
+
+/**
+ * 写入按主键分组的表
+ */
+fn write_table(contents: &mut String, table: &LuaTable, depth: usize) {
+    contents.push_str("{\n");
+    for (key, node) in table.entries.iter() {
+        push_indent(contents, depth + 1);
+        write_key(contents, key);
+        contents.push_str(" = ");
+        match node {
+            LuaNode::Row(row_data) => write_row(contents, row_data),
+            LuaNode::Table(child) => write_table(contents, child, depth + 1),
+        }
+        contents.push_str(",\n");
+    }
+    push_indent(contents, depth);
+    contents.push('}');
+}
+
+/**
+ * 写入一行数据，写在同一行
+ */
+fn write_row(contents: &mut String, row_data: &Map<String, Value>) {
+    contents.push_str("{ ");
+    for (index, (field_name, value)) in row_data.iter().enumerate() {
+        if index > 0 {
+            contents.push_str(", ");
+        }
+        write_key(contents, &Value::String(field_name.clone()));
+        contents.push_str(" = ");
+        write_value(contents, value);
+    }
+    contents.push_str(" }");
+}
+
+/**
+ * 写入表的键，合法的标识符直接写入，其余使用`[...]`
+ */
+fn write_key(contents: &mut String, key: &Value) {
+    match key {
+        Value::String(s) if is_identifier(s) => contents.push_str(s),
+        _ => {
+            contents.push('[');
+            write_value(contents, key);
+            contents.push(']');
+        }
+    }
+}
+
+/**
+ * 写入值，向量和数组写为嵌套表
+ */
+fn write_value(contents: &mut String, value: &Value) {
+    match value {
+        Value::Null => contents.push_str("nil"),
+        Value::Bool(b) => contents.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => contents.push_str(&n.to_string()),
+        Value::String(s) => write_string(contents, s),
+        Value::Array(items) => {
+            contents.push('{');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    contents.push_str(", ");
+                }
+                write_value(contents, item);
+            }
+            contents.push('}');
+        }
+        Value::Object(object) => write_row(contents, object),
+    }
+}
+
+/**
+ * 写入转义后的字符串
+ */
+fn write_string(contents: &mut String, value: &str) {
+    contents.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => contents.push_str("\\\\"),
+            '"' => contents.push_str("\\\""),
+            '\n' => contents.push_str("\\n"),
+            '\r' => contents.push_str("\\r"),
+            '\t' => contents.push_str("\\t"),
+            '\0'..='\x1f' | '\x7f' => contents.push_str(&format!("\\{:03}", c as u32)),
+            _ => contents.push(c),
+        }
+    }
+    contents.push('"');
+}
+
+/**
+ * 是否是合法的Lua标识符
+ */
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !LUA_KEYWORDS.contains(&name)
+}
+
+/**
+ * 写入缩进
+ */
+fn push_indent(contents: &mut String, depth: usize) {
+    for _ in 0..depth {
+        contents.push_str("    ");
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    common::{output, setting::BuildSetting},
+    gui::datas::{
+        emsgpack_layout_type::EMsgPackLayoutType, esheet_type::ESheetType, table_data::TableData,
+    },
+};
+use serde_json::{Map, Value};
+use std::{io::Error, path::PathBuf};
+
+/**
+ * 将数据转换为MessagePack
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
+        // 枚举不导出
+        return;
+    }
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.msgpack", table_data.file_name));
+    let json_data: &[Map<String, Value>] = &table_data.rows;
+    if json_data.is_empty() {
+        log::debug!("No data to export: {}", target_path.to_str().unwrap());
+        output::remove(build_setting, &target_path);
+        return;
+    }
+    let value: Value = match build_setting.msgpack_layout {
+        EMsgPackLayoutType::Maps => {
+            if table_data.gable_type == ESheetType::KV {
+                Value::Object(json_data[0].clone())
+            } else {
+                Value::Array(json_data.iter().cloned().map(Value::Object).collect())
+            }
+        }
+        EMsgPackLayoutType::Arrays => {
+            let field_names: Vec<&str> = get_field_names(table_data);
+            if table_data.gable_type == ESheetType::KV {
+                to_row(&field_names, &json_data[0])
+            } else {
+                Value::Array(
+                    json_data
+                        .iter()
+                        .map(|row_data| to_row(&field_names, row_data))
+                        .collect(),
+                )
+            }
+        }
+    };
+    let contents: Vec<u8> = rmp_serde::to_vec(&value).expect("MessagePack serialization failed");
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, contents);
+    if result.is_err() {
+        log::error!(
+            "Export [{}] failed: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    }
+}
+
+/**
+ * 获取字段顺序，与生成的脚本中的字段顺序一致
+ * @param table_data 表数据
+ * @return 字段名列表
+ */
+fn get_field_names(table_data: &TableData) -> Vec<&str> {
+    table_data
+        .field_info
+        .fields
+        .iter()
+        .map(|field| field.field_name.as_str())
+        .collect()
+}
+
+/**
+ * 按字段顺序将一行数据转换为数组，缺少的字段为nil
+ * @param field_names 字段名列表
+ * @param row_data 行数据
+ * @return 数组
+ */
+fn to_row(field_names: &[&str], row_data: &Map<String, Value>) -> Value {
+    Value::Array(
+        field_names
+            .iter()
+            .map(|field_name| row_data.get(*field_name).cloned().unwrap_or(Value::Null))
+            .collect(),
+    )
+}
@@ -1,76 +1,144 @@
 use crate::{
     common::{
-        generate::{generate::GenerateFieldInfo, proto_field_info},
+        constant,
+        convert::convert,
+        generate::{generate::GenerateFieldInfo, proto_field_info, proto_registry},
+        output,
         setting::BuildSetting,
         utils,
     },
     gui::datas::{
-        esheet_type::ESheetType,
-        tree_data::{FieldInfo, TreeData},
+        esheet_type::ESheetType, etarget_type::ETargetType, table_data::TableData,
+        tree_data::TreeData,
     },
 };
 use serde_json::{Map, Value};
-use std::{error::Error, path::PathBuf};
+use std::{borrow::Cow, collections::BTreeMap, error::Error, path::Path, path::PathBuf};
 
 /**
  * 将数据转换为protobuff
  * @param build_setting 构建设置
- * @param tree_data 树数据
+ * @param source 覆盖数据和字段命名之前的树数据，用于同步字段编号表
+ * @param table_data 表数据
 */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    if tree_data.gable_type == ESheetType::Enum {
+pub fn to(build_setting: &BuildSetting, source: &TreeData, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
         return;
     }
-    let value_data: Vec<Map<String, Value>> = tree_data.to_values(&build_setting.keyword);
-    if value_data.is_empty() || value_data.len() <= 0 {
-        log::debug!("Data is empty");
-        return;
-    }
-    let field_info: FieldInfo = if let Some(info) = tree_data.to_fields(&build_setting.keyword) {
-        info
+    let encoded: Vec<u8> = if let Some(encoded) = encode_table(build_setting, source, table_data, 1)
+    {
+        encoded
     } else {
         return;
     };
+    let target_path: PathBuf = utils::get_absolute_path(&build_setting.target_path)
+        .join(format!("{}.bytes", table_data.file_name));
+    write_bytes(build_setting, &table_data.file_name, &target_path, &encoded);
+}
 
-    let (_, proto_fields, _) = proto_field_info::transition_fields(&field_info, true);
+/**
+ * 将所有表的数据汇总写入一个二进制文件，对应生成的数据库消息
+ * @param build_setting 构建设置
+ * @param datas 表名和表数据的映射
+*/
+pub fn to_bundle(build_setting: &BuildSetting, datas: &BTreeMap<String, Cow<TreeData>>) {
+    if build_setting.target_type != ETargetType::Protobuff || !build_setting.proto_bundle {
+        return;
+    }
+    let (_, bundle_info, _) = proto_field_info::transition_bundle(build_setting, datas);
+    let mut buffer: Vec<u8> = Vec::new();
+    for field in bundle_info.fields.iter() {
+        let source: &TreeData = match datas.values().find(|d| d.file_name == field.field_desc) {
+            Some(source) => source,
+            None => continue,
+        };
+        let tree_data: Cow<TreeData> = match convert::transform(build_setting, source) {
+            Some(tree_data) => tree_data,
+            None => continue,
+        };
+        let table_data: TableData = match TableData::new(&tree_data, &build_setting.keyword) {
+            Some(table_data) => table_data,
+            None => continue,
+        };
+        let field_number: u32 = field.field_index as u32;
+        let encoded: Vec<u8> =
+            if let Some(encoded) = encode_table(build_setting, source, &table_data, field_number) {
+                encoded
+            } else {
+                continue;
+            };
+        if table_data.gable_type == ESheetType::KV {
+            // KV表作为单个消息字段
+            let key: u32 = (field_number << 3) | 2;
+            encode_varint(key as u64, &mut buffer);
+            encode_varint(encoded.len() as u64, &mut buffer);
+        }
+        buffer.extend_from_slice(&encoded);
+    }
     let target_path: PathBuf = utils::get_absolute_path(&build_setting.target_path)
-        .join(format!("{}.bytes", tree_data.file_name));
-    match tree_data.gable_type {
+        .join(format!("{}.bytes", constant::PROTO_BUNDLE_NAME));
+    write_bytes(
+        build_setting,
+        constant::PROTO_BUNDLE_NAME,
+        &target_path,
+        &buffer,
+    );
+}
+
+/**
+ * 将单个表编码为二进制数据
+ * @param build_setting 构建设置
+ * @param source 覆盖数据和字段命名之前的树数据，用于同步字段编号表
+ * @param table_data 表数据
+ * @param items_field_number 普通表中行数据所在的字段编号
+ * @return 二进制数据，没有数据时返回None
+ */
+fn encode_table(
+    build_setting: &BuildSetting,
+    source: &TreeData,
+    table_data: &TableData,
+    items_field_number: u32,
+) -> Option<Vec<u8>> {
+    let value_data: &[Map<String, Value>] = &table_data.rows;
+    if value_data.is_empty() {
+        log::debug!("Data is empty");
+        return None;
+    }
+    let (_, mut proto_fields, _) =
+        proto_field_info::transition_fields(&table_data.field_info, true);
+    proto_registry::apply(build_setting, source, &mut proto_fields);
+    let result: Result<Vec<u8>, Box<dyn Error>> = match table_data.gable_type {
         ESheetType::Normal | ESheetType::Localize => {
-            if let Ok(encoded) = encode_normal_data(&value_data, &proto_fields) {
-                if let Err(e) = std::fs::write(&target_path, &encoded) {
-                    log::error!(
-                        "Normal table {} failed to be written to binary file: {}",
-                        tree_data.file_name,
-                        e
-                    );
-                } else {
-                    log::info!(
-                        "Export [{}] Protobuf binary data successful: {}",
-                        build_setting.display_name,
-                        target_path.to_str().unwrap()
-                    );
-                }
-            }
+            encode_normal_data(value_data, &proto_fields, items_field_number)
         }
-        ESheetType::KV => {
-            if let Ok(encoded) = encode_kv_data(&value_data[0], &proto_fields) {
-                if let Err(e) = std::fs::write(&target_path, &encoded) {
-                    log::error!(
-                        "The writing of KV table [{}] to the binary file failed: {}",
-                        tree_data.file_name,
-                        e
-                    );
-                } else {
-                    log::info!(
-                        "Export [{}] Protobuf binary data successful: {}",
-                        build_setting.display_name,
-                        target_path.to_str().unwrap()
-                    );
-                }
-            }
+        ESheetType::KV => encode_kv_data(&value_data[0], &proto_fields),
+        ESheetType::Enum => return None,
+    };
+    match result {
+        Ok(encoded) => Some(encoded),
+        Err(e) => {
+            log::error!("Failed to encode {}: {}", table_data.file_name, e);
+            None
         }
-        _ => {}
+    }
+}
+
+/**
+ * 写入二进制文件
+ */
+fn write_bytes(build_setting: &BuildSetting, table_name: &str, target_path: &Path, encoded: &[u8]) {
+    if let Err(e) = output::write(build_setting, target_path, encoded) {
+        log::error!(
+            "Table {} failed to be written to binary file: {}",
+            table_name,
+            e
+        );
+    } else {
+        log::info!(
+            "Export [{}] Protobuf binary data successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
     }
 }
 
@@ -78,20 +146,21 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
  * 将普通数据表转换为Protobuf二进制数据
  * @param items 数据
  * @param fields 字段数据
+ * @param items_field_number 行数据所在的字段编号
  * @return 二进制数据
  */
 fn encode_normal_data(
-    items: &Vec<Map<String, Value>>,
+    items: &[Map<String, Value>],
     info: &GenerateFieldInfo,
+    items_field_number: u32,
 ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
     let mut table_buffer = Vec::new();
-    let items_field_number = 1u32;
 
     // 为每个数据项编码并作为repeated字段的元素添加
     for item in items.iter() {
         let mut item_buffer = Vec::new();
-        for (index, field_info) in info.fields.iter().enumerate() {
-            let field_number: u32 = (index + 1) as u32;
+        for field_info in info.fields.iter() {
+            let field_number: u32 = field_info.field_index as u32;
             if let Some(value) = item.get(&field_info.field_name) {
                 encode_field_value(field_number, value, &field_info.data_type, &mut item_buffer)?;
             }
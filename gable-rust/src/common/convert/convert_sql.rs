@@ -0,0 +1,280 @@
+use crate::{
+    common::{output, setting::BuildSetting},
+    gui::datas::{
+        edata_type::EDataType, esheet_type::ESheetType, esql_dialect_type::ESqlDialectType,
+        table_data::TableData, tree_data::FieldInfo,
+    },
+};
+use serde_json::{Map, Value};
+use std::{io::Error, path::PathBuf};
+
+/**
+ * 每条INSERT语句包含的最大行数
+ */
+const INSERT_BATCH_SIZE: usize = 100;
+
+/**
+ * 将数据转换为SQL脚本（建表语句和INSERT语句）
+ * 每次先删除再重建表，表结构变化（增删列、修改类型）后脚本仍可重复执行
+ * 有主键的表使用upsert，表格中重复的主键保留最后一行
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
+        // 枚举不导出
+        return;
+    }
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.sql", table_data.file_name));
+    let contents: String = to_sql(
+        build_setting.sql_dialect,
+        &table_data.file_name,
+        &table_data.field_info,
+        &table_data.rows,
+    );
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, contents);
+    if result.is_err() {
+        log::error!(
+            "Export [{}] failed: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    }
+}
+
+/**
+ * 生成单个表的SQL脚本
+ * @param dialect SQL方言
+ * @param table_name 表名
+ * @param field_info 字段信息
+ * @param values 行数据
+ * @return SQL脚本
+ */
+fn to_sql(
+    dialect: ESqlDialectType,
+    table_name: &str,
+    field_info: &FieldInfo,
+    values: &[Map<String, Value>],
+) -> String {
+    let table: String = quote(dialect, table_name);
+    let primary_keys: Vec<&str> = field_info
+        .main_fields
+        .iter()
+        .map(|field| field.field_name.as_str())
+        .collect();
+    let columns: Vec<String> = field_info
+        .fields
+        .iter()
+        .map(|field| quote(dialect, &field.field_name))
+        .collect();
+
+    let mut contents: String = String::new();
+    // 建表
+    let mut definitions: Vec<String> = field_info
+        .fields
+        .iter()
+        .map(|field| {
+            if field.field_type == EDataType::Unknown {
+                log::warn!(
+                    "Field {} of {} has an unknown data type and is exported as text",
+                    field.field_name,
+                    table_name
+                );
+            }
+            let primary: bool = primary_keys.contains(&field.field_name.as_str());
+            let mut definition: String = format!(
+                "  {} {}",
+                quote(dialect, &field.field_name),
+                get_column_type(dialect, &field.field_type, primary)
+            );
+            if primary {
+                definition.push_str(" NOT NULL");
+            }
+            if dialect == ESqlDialectType::MySQL && !field.field_desc.is_empty() {
+                definition.push_str(&format!(
+                    " COMMENT {}",
+                    quote_string(dialect, &field.field_desc)
+                ));
+            }
+            definition
+        })
+        .collect();
+    if !primary_keys.is_empty() {
+        let keys: Vec<String> = primary_keys.iter().map(|key| quote(dialect, key)).collect();
+        definitions.push(format!("  PRIMARY KEY ({})", keys.join(", ")));
+    }
+    contents.push_str(&format!("DROP TABLE IF EXISTS {};\n", table));
+    contents.push_str(&format!(
+        "CREATE TABLE {} (\n{}\n);\n",
+        table,
+        definitions.join(",\n")
+    ));
+    if dialect == ESqlDialectType::PostgreSQL {
+        for field in field_info.fields.iter() {
+            if field.field_desc.is_empty() {
+                continue;
+            }
+            contents.push_str(&format!(
+                "COMMENT ON COLUMN {}.{} IS {};\n",
+                table,
+                quote(dialect, &field.field_name),
+                quote_string(dialect, &field.field_desc)
+            ));
+        }
+    }
+    if values.is_empty() || columns.is_empty() {
+        return contents;
+    }
+
+    // upsert时更新的列
+    let update_columns: Vec<String> = field_info
+        .fields
+        .iter()
+        .filter(|field| !primary_keys.contains(&field.field_name.as_str()))
+        .map(|field| {
+            let column: String = quote(dialect, &field.field_name);
+            match dialect {
+                ESqlDialectType::MySQL => format!("{} = VALUES({})", column, column),
+                ESqlDialectType::PostgreSQL => format!("{} = EXCLUDED.{}", column, column),
+            }
+        })
+        .collect();
+    for batch in values.chunks(INSERT_BATCH_SIZE) {
+        let rows: Vec<String> = batch
+            .iter()
+            .map(|row_data| {
+                let row: Vec<String> = field_info
+                    .fields
+                    .iter()
+                    .map(|field| to_literal(dialect, row_data.get(&field.field_name)))
+                    .collect();
+                format!("  ({})", row.join(", "))
+            })
+            .collect();
+        contents.push_str(&format!(
+            "INSERT INTO {} ({}) VALUES\n{}",
+            table,
+            columns.join(", "),
+            rows.join(",\n")
+        ));
+        if !primary_keys.is_empty() {
+            match dialect {
+                ESqlDialectType::MySQL => {
+                    if update_columns.is_empty() {
+                        // 只有主键列时保持原样
+                        let key: String = quote(dialect, primary_keys[0]);
+                        contents.push_str(&format!("\nON DUPLICATE KEY UPDATE {} = {}", key, key));
+                    } else {
+                        contents.push_str(&format!(
+                            "\nON DUPLICATE KEY UPDATE {}",
+                            update_columns.join(", ")
+                        ));
+                    }
+                }
+                ESqlDialectType::PostgreSQL => {
+                    let keys: Vec<String> =
+                        primary_keys.iter().map(|key| quote(dialect, key)).collect();
+                    if update_columns.is_empty() {
+                        contents
+                            .push_str(&format!("\nON CONFLICT ({}) DO NOTHING", keys.join(", ")));
+                    } else {
+                        contents.push_str(&format!(
+                            "\nON CONFLICT ({}) DO UPDATE SET {}",
+                            keys.join(", "),
+                            update_columns.join(", ")
+                        ));
+                    }
+                }
+            }
+        }
+        contents.push_str(";\n");
+    }
+    contents
+}
+
+/**
+ * 获取字段类型对应的列类型，数组和向量以JSON保存
+ * @param dialect SQL方言
+ * @param data_type 字段类型
+ * @param primary 是否主键，MySQL的主键字符串需要限定长度
+ * @return 列类型
+ */
+fn get_column_type(dialect: ESqlDialectType, data_type: &EDataType, primary: bool) -> &'static str {
+    match data_type {
+        EDataType::Int | EDataType::Time | EDataType::Enum => "INTEGER",
+        EDataType::Long | EDataType::Date => "BIGINT",
+        EDataType::Boolean => "BOOLEAN",
+        EDataType::Float | EDataType::Percentage | EDataType::Permillage | EDataType::Permian => {
+            match dialect {
+                ESqlDialectType::MySQL => "FLOAT",
+                ESqlDialectType::PostgreSQL => "REAL",
+            }
+        }
+        EDataType::Unknown | EDataType::String | EDataType::Loc => match dialect {
+            ESqlDialectType::MySQL if primary => "VARCHAR(255)",
+            _ => "TEXT",
+        },
+        EDataType::Vector2
+        | EDataType::Vector3
+        | EDataType::Vector4
+        | EDataType::IntArr
+        | EDataType::LongArr
+        | EDataType::StringArr
+        | EDataType::BooleanArr
+        | EDataType::FloatArr
+        | EDataType::Vector2Arr
+        | EDataType::Vector3Arr
+        | EDataType::Vector4Arr => match dialect {
+            ESqlDialectType::MySQL => "JSON",
+            ESqlDialectType::PostgreSQL => "JSONB",
+        },
+    }
+}
+
+/**
+ * JSON值转换为SQL字面量，数组和对象转换为JSON字符串
+ * @param dialect SQL方言
+ * @param value JSON值
+ * @return SQL字面量
+ */
+fn to_literal(dialect: ESqlDialectType, value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => "NULL".to_string(),
+        Some(Value::Bool(b)) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Some(Value::Number(n)) => n.to_string(),
+        Some(Value::String(s)) => quote_string(dialect, s),
+        Some(value) => quote_string(dialect, &value.to_string()),
+    }
+}
+
+/**
+ * 标识符加引号
+ */
+fn quote(dialect: ESqlDialectType, name: &str) -> String {
+    match dialect {
+        ESqlDialectType::MySQL => format!("`{}`", name.replace('`', "``")),
+        ESqlDialectType::PostgreSQL => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+/**
+ * 字符串加引号并转义，MySQL默认会将反斜杠作为转义符
+ */
+fn quote_string(dialect: ESqlDialectType, value: &str) -> String {
+    let escaped: String = match dialect {
+        ESqlDialectType::MySQL => value.replace('\\', "\\\\").replace('\'', "''"),
+        ESqlDialectType::PostgreSQL => value.replace('\'', "''"),
+    };
+    format!("'{}'", escaped)
+}
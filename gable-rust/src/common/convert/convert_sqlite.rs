@@ -0,0 +1,320 @@
+use crate::{
+    common::{constant, output, setting::BuildSetting, utils},
+    gui::datas::{
+        edata_type::EDataType, esheet_type::ESheetType, table_data::TableData, tree_data::FieldInfo,
+    },
+};
+use rusqlite::{
+    Connection, OptionalExtension, Transaction, params_from_iter, types::Value as SqlValue,
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::PathBuf,
+    sync::{LazyLock, Mutex},
+};
+
+/**
+ * 写入数据库时持有的锁，所有表写入同一个数据库文件，并行导出时需要逐个写入
+ */
+static DATABASE_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/**
+ * 将数据写入SQLite数据库，所有表写入同一个数据库文件，每个表单对应一张数据表
+ * 普通表和KV表按字段类型建列；枚举表导出为查找表(name, value, desc)；本地化表导出为翻译表(主键, language, text)，以(主键, language)为主键
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    let target_path: PathBuf = utils::get_absolute_path(&build_setting.target_path);
+    let db_path: PathBuf = target_path.join(constant::SQLITE_DATABASE_NAME);
+    // 导出目录不存在时无法创建数据库文件
+    if let Err(e) = fs::create_dir_all(&target_path) {
+        log::error!(
+            "Export [{}] failed: {} - {}",
+            build_setting.display_name,
+            db_path.to_str().unwrap(),
+            e
+        );
+        return;
+    }
+    let _lock = DATABASE_LOCK.lock().unwrap();
+    let result: rusqlite::Result<(usize, bool)> = match Connection::open(&db_path) {
+        Ok(mut conn) => write_table(&mut conn, table_data),
+        Err(e) => Err(e),
+    };
+    if let Ok((_, written)) = result {
+        output::record(build_setting, &db_path, written);
+    }
+    match result {
+        Ok((rows, false)) => log::debug!(
+            "Export [{}] unchanged: {} -> {} ({} rows)",
+            build_setting.display_name,
+            table_data.file_name,
+            db_path.to_str().unwrap(),
+            rows
+        ),
+        Ok((rows, true)) => log::info!(
+            "Export [{}] successful: {} -> {} ({} rows)",
+            build_setting.display_name,
+            table_data.file_name,
+            db_path.to_str().unwrap(),
+            rows
+        ),
+        Err(e) => log::error!(
+            "Export [{}] failed: {} -> {} - {}",
+            build_setting.display_name,
+            table_data.file_name,
+            db_path.to_str().unwrap(),
+            e
+        ),
+    }
+}
+
+/**
+ * 删除数据库中不再对应任何表的数据表，完整导出后调用；构建设置开启了stale_dry_run时只列出不删除
+ * @param build_setting 构建设置
+ * @param table_names 当前所有表的表名
+ */
+pub fn clean_stale(build_setting: &BuildSetting, table_names: &BTreeSet<&str>) {
+    let db_path: PathBuf =
+        utils::get_absolute_path(&build_setting.target_path).join(constant::SQLITE_DATABASE_NAME);
+    if !db_path.is_file() {
+        return;
+    }
+    let _lock = DATABASE_LOCK.lock().unwrap();
+    let result: rusqlite::Result<Vec<String>> = Connection::open(&db_path).and_then(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")?;
+        let names: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        let stale: Vec<String> = names
+            .into_iter()
+            .filter(|name| !name.starts_with("sqlite_") && !table_names.contains(name.as_str()))
+            .collect();
+        if !build_setting.stale_dry_run {
+            for name in stale.iter() {
+                conn.execute(&format!("DROP TABLE IF EXISTS {}", quote(name)), [])?;
+            }
+        }
+        Ok(stale)
+    });
+    match result {
+        Ok(stale) => {
+            for name in stale.iter() {
+                if build_setting.stale_dry_run {
+                    log::warn!(
+                        "Stale output [{}] (dry run): {} -> {}",
+                        build_setting.display_name,
+                        name,
+                        db_path.to_str().unwrap()
+                    );
+                } else {
+                    log::info!(
+                        "Export [{}] removed: {} -> {}",
+                        build_setting.display_name,
+                        name,
+                        db_path.to_str().unwrap()
+                    );
+                    output::record_removed(build_setting);
+                }
+            }
+        }
+        Err(e) => log::error!(
+            "Failed to clean stale tables of {}: {}",
+            db_path.to_str().unwrap(),
+            e
+        ),
+    }
+}
+
+/**
+ * 在一个事务中重建数据表并写入数据，表结构和数据都没有变化时不修改数据库
+ * @return (行数, 是否写入)
+ */
+fn write_table(conn: &mut Connection, table_data: &TableData) -> rusqlite::Result<(usize, bool)> {
+    let field_info: &FieldInfo = &table_data.field_info;
+    // 字段名，字段类型，是否主键
+    let mut columns: Vec<(String, &str, bool)> = Vec::new();
+    let mut rows: Vec<Vec<SqlValue>> = Vec::new();
+    match table_data.gable_type {
+        ESheetType::Enum => {
+            columns.push(("name".to_string(), "TEXT", true));
+            columns.push(("value".to_string(), "INTEGER", false));
+            columns.push(("desc".to_string(), "TEXT", false));
+            for field in field_info.fields.iter() {
+                rows.push(vec![
+                    SqlValue::Text(field.field_name.clone()),
+                    SqlValue::Integer(field.field_index as i64),
+                    SqlValue::Text(field.field_desc.clone()),
+                ]);
+            }
+        }
+        ESheetType::Localize => {
+            let keys: Vec<&str> = table_data.primary_keys();
+            for key in keys.iter() {
+                columns.push((key.to_string(), "TEXT", true));
+            }
+            columns.push(("language".to_string(), "TEXT", true));
+            columns.push(("text".to_string(), "TEXT", false));
+            for row_data in table_data.rows.iter() {
+                let key_values: Vec<SqlValue> = keys
+                    .iter()
+                    .map(|key| to_sql_value(row_data.get(*key)))
+                    .collect();
+                for (language, text) in row_data.iter() {
+                    if keys.contains(&language.as_str()) {
+                        continue;
+                    }
+                    let mut row: Vec<SqlValue> = key_values.clone();
+                    row.push(SqlValue::Text(language.clone()));
+                    row.push(to_sql_value(Some(text)));
+                    rows.push(row);
+                }
+            }
+        }
+        ESheetType::Normal | ESheetType::KV => {
+            for field in field_info.fields.iter() {
+                let primary: bool = table_data.gable_type == ESheetType::Normal
+                    && field_info
+                        .main_fields
+                        .iter()
+                        .any(|main| main.field_name == field.field_name);
+                columns.push((
+                    field.field_name.clone(),
+                    get_column_type(&field.field_type),
+                    primary,
+                ));
+            }
+            for row_data in table_data.rows.iter() {
+                rows.push(
+                    columns
+                        .iter()
+                        .map(|(field_name, _, _)| to_sql_value(row_data.get(field_name)))
+                        .collect(),
+                );
+            }
+        }
+    }
+
+    let table_name: String = quote(&table_data.file_name);
+    let mut definitions: Vec<String> = columns
+        .iter()
+        .map(|(field_name, column_type, _)| format!("{} {}", quote(field_name), column_type))
+        .collect();
+    let primary_keys: Vec<String> = columns
+        .iter()
+        .filter(|(_, _, primary)| *primary)
+        .map(|(field_name, _, _)| quote(field_name))
+        .collect();
+    if !primary_keys.is_empty() {
+        definitions.push(format!("PRIMARY KEY ({})", primary_keys.join(", ")));
+    }
+    let create_sql: String = format!("CREATE TABLE {} ({})", table_name, definitions.join(", "));
+
+    let tx: Transaction = conn.transaction()?;
+    if is_table_unchanged(&tx, &table_data.file_name, &create_sql, &rows)? {
+        return Ok((rows.len(), false));
+    }
+    tx.execute(&format!("DROP TABLE IF EXISTS {}", table_name), [])?;
+    tx.execute(&create_sql, [])?;
+    {
+        let placeholders: Vec<&str> = vec!["?"; columns.len()];
+        let mut stmt = tx.prepare(&format!(
+            "INSERT INTO {} VALUES ({})",
+            table_name,
+            placeholders.join(", ")
+        ))?;
+        for row in rows.iter() {
+            stmt.execute(params_from_iter(row.iter()))?;
+        }
+    }
+    tx.commit()?;
+    Ok((rows.len(), true))
+}
+
+/**
+ * 数据库中的数据表是否与要写入的结构和数据相同
+ * @param tx 事务
+ * @param table_name 表名
+ * @param create_sql 建表语句
+ * @param rows 要写入的行数据
+ * @return 是否相同
+ */
+fn is_table_unchanged(
+    tx: &Transaction,
+    table_name: &str,
+    create_sql: &str,
+    rows: &[Vec<SqlValue>],
+) -> rusqlite::Result<bool> {
+    let existing_sql: Option<String> = tx
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [table_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if existing_sql.as_deref() != Some(create_sql) {
+        return Ok(false);
+    }
+    let mut stmt = tx.prepare(&format!(
+        "SELECT * FROM {} ORDER BY rowid",
+        quote(table_name)
+    ))?;
+    let column_count: usize = stmt.column_count();
+    let existing: Vec<Vec<SqlValue>> = stmt
+        .query_map([], |row| {
+            (0..column_count)
+                .map(|index| row.get::<_, SqlValue>(index))
+                .collect()
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(existing == rows)
+}
+
+/**
+ * 获取字段类型对应的SQLite列类型，数组和向量以JSON文本保存
+ * @param data_type 字段类型
+ * @return 列类型
+ */
+fn get_column_type(data_type: &EDataType) -> &'static str {
+    match data_type {
+        EDataType::Int
+        | EDataType::Long
+        | EDataType::Boolean
+        | EDataType::Time
+        | EDataType::Date
+        | EDataType::Enum => "INTEGER",
+        EDataType::Float | EDataType::Percentage | EDataType::Permillage | EDataType::Permian => {
+            "REAL"
+        }
+        _ => "TEXT",
+    }
+}
+
+/**
+ * JSON值转换为SQLite值，数组和对象转换为JSON文本
+ * @param value JSON值
+ * @return SQLite值
+ */
+fn to_sql_value(value: Option<&Value>) -> SqlValue {
+    match value {
+        None | Some(Value::Null) => SqlValue::Null,
+        Some(Value::Bool(b)) => SqlValue::Integer(*b as i64),
+        Some(Value::Number(n)) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Some(Value::String(s)) => SqlValue::Text(s.clone()),
+        Some(value) => SqlValue::Text(value.to_string()),
+    }
+}
+
+/**
+ * 标识符加引号
+ */
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
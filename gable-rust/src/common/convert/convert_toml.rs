@@ -0,0 +1,166 @@
+use crate::{
+    common::{output, setting::BuildSetting},
+    gui::datas::{esheet_type::ESheetType, table_data::TableData},
+};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, io::Error, path::PathBuf};
+
+/**
+ * 将数据转换为toml
+ * KV表的每个键对应toml的一个键，普通表导出为以表名命名的表数组`[[表名]]`，字段描述写为注释
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
+        // 枚举不导出
+        return;
+    }
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.toml", table_data.file_name));
+    let values: &[Map<String, Value>] = &table_data.rows;
+    if values.is_empty() {
+        log::debug!("No data to export: {}", target_path.to_str().unwrap());
+        output::remove(build_setting, &target_path);
+        return;
+    }
+    let descs: HashMap<&str, &str> = get_field_descs(table_data);
+
+    let mut contents: String = String::new();
+    if table_data.gable_type == ESheetType::KV {
+        for (field_name, value) in values[0].iter() {
+            if value.is_null() {
+                continue;
+            }
+            write_comment(&mut contents, descs.get(field_name.as_str()));
+            contents.push_str(&format!("{} = {}\n", to_key(field_name), to_value(value)));
+        }
+    } else {
+        // 字段描述只在文件开头写一次
+        for field in table_data.field_info.fields.iter() {
+            if !field.field_desc.is_empty() {
+                contents.push_str(&format!(
+                    "# {}: {}\n",
+                    field.field_name,
+                    single_line(&field.field_desc)
+                ));
+            }
+        }
+        let table_name: String = to_key(&table_data.file_name);
+        for row_data in values.iter() {
+            contents.push_str(&format!("\n[[{}]]\n", table_name));
+            for (field_name, value) in row_data.iter() {
+                if value.is_null() {
+                    continue;
+                }
+                contents.push_str(&format!("{} = {}\n", to_key(field_name), to_value(value)));
+            }
+        }
+    }
+
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, contents);
+    if result.is_err() {
+        log::error!(
+            "Export [{}] failed: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    }
+}
+
+/**
+ * 获取字段描述
+ * @param table_data 表数据
+ * @return 字段名 -> 描述
+ */
+pub fn get_field_descs(table_data: &TableData) -> HashMap<&str, &str> {
+    table_data
+        .field_info
+        .fields
+        .iter()
+        .filter(|field| !field.field_desc.is_empty())
+        .map(|field| (field.field_name.as_str(), field.field_desc.as_str()))
+        .collect()
+}
+
+/**
+ * 描述中的换行和控制字符（包括DEL）替换为空格，保证注释只占一行且是合法的注释内容
+ */
+pub fn single_line(desc: &str) -> String {
+    desc.replace("\r\n", " ")
+        .chars()
+        .map(|c| if c.is_control() && c != '\t' { ' ' } else { c })
+        .collect()
+}
+
+/**
+ * 转换为双引号字符串，使用json的转义规则，并将json不转义的DEL（U+007F）转义为`\u007F`
+ * toml的基本字符串和yaml的双引号字符串都不允许未转义的DEL
+ */
+pub fn to_quoted(text: &str) -> String {
+    Value::String(text.to_string())
+        .to_string()
+        .replace('\u{7f}', "\\u007F")
+}
+
+/**
+ * 写入注释
+ */
+fn write_comment(contents: &mut String, desc: Option<&&str>) {
+    if let Some(desc) = desc {
+        contents.push_str(&format!("# {}\n", single_line(desc)));
+    }
+}
+
+/**
+ * 键名，只包含字母、数字、`_`和`-`时使用裸键，否则使用带引号的键
+ */
+fn to_key(name: &str) -> String {
+    let bare: bool = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        name.to_string()
+    } else {
+        to_quoted(name)
+    }
+}
+
+/**
+ * 值转换为toml字面量，向量转换为内联表，数组中的空值会被忽略
+ * toml的基本字符串转义规则与json兼容，DEL需要额外转义
+ */
+fn to_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::String(text) => to_quoted(text),
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .filter(|item| !item.is_null())
+                .map(to_value)
+                .collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(object) => {
+            let items: Vec<String> = object
+                .iter()
+                .filter(|(_, item)| !item.is_null())
+                .map(|(key, item)| format!("{} = {}", to_key(key), to_value(item)))
+                .collect();
+            format!("{{ {} }}", items.join(", "))
+        }
+    }
+}
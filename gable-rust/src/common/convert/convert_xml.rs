@@ -1,38 +1,43 @@
 use crate::{
-    common::{constant, setting::BuildSetting, utils},
-    gui::datas::{cell_data::CellData, esheet_type::ESheetType, tree_data::TreeData},
-};
-use std::{
-    fs::File,
-    io::{BufWriter, Error, Write},
-    path::PathBuf,
+    common::{convert::convert_xml_schema, output, setting::BuildSetting},
+    gui::datas::{
+        esheet_type::ESheetType,
+        exml_layout_type::EXmlLayoutType,
+        table_data::{self, TableData},
+    },
 };
+use serde_json::{Map, Value};
+use std::path::PathBuf;
 
 /**
  * xml 转换
  * @param build_setting 构建设置
- * @param tree_data 树数据
+ * @param table_data 表数据
  * */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    if tree_data.gable_type == ESheetType::Enum {
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
         // 枚举不导出
         return;
     }
 
-    let target_path: PathBuf = utils::get_absolute_path(&build_setting.target_path)
-        .join(format!("{}.xml", tree_data.file_name));
-    let xml_data: String = to_xml_data(tree_data, &build_setting.keyword);
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.xml", table_data.file_name));
+    let xml_data: String = to_xml_data(build_setting, table_data);
     if xml_data.is_empty() {
         log::debug!(
             "Export [{}] skipped: {}",
             build_setting.display_name,
             target_path.to_str().unwrap()
         );
+        output::remove(build_setting, &target_path);
         return;
     }
-    // 创建XML文件
-    let file: Result<File, Error> = File::create(&target_path);
-    if file.is_err() {
+    // 写入XML文件
+    if output::write(build_setting, &target_path, xml_data).is_err() {
         log::error!(
             "Export [{}] failed: {}",
             build_setting.display_name,
@@ -40,41 +45,28 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
         );
         return;
     }
-    let file: File = file.unwrap();
-    let mut writer: BufWriter<File> = BufWriter::new(file);
-
-    // 写入XML数据
-    if let Err(e) = writer.write_all(xml_data.as_bytes()) {
-        log::error!("Error writing [{}] file: {}", build_setting.display_name, e);
-        return;
-    }
-
-    if let Err(e) = writer.flush() {
-        log::error!(
-            "Error flushing [{}] file: {}",
-            build_setting.display_name,
-            e
-        );
-        return;
-    }
 
     log::info!(
         "Export [{}] successful: {}",
         build_setting.display_name,
         target_path.to_str().unwrap()
     );
+    if build_setting.xml_schema {
+        convert_xml_schema::to(build_setting, table_data);
+    }
 }
 
 /**
  *  转换为xml数据
- *  @param tree_data 树数据
- *  @param keyword 关键字
+ *  @param build_setting 构建设置
+ *  @param table_data 表数据
  * */
-fn to_xml_data(tree_data: &TreeData, keyword: &str) -> String {
-    match tree_data.gable_type {
-        ESheetType::Normal => normal_xml_data(tree_data, keyword),
-        ESheetType::KV => kv_xml_data(tree_data, keyword),
-        ESheetType::Localize => localize_xml_data(tree_data, keyword),
+fn to_xml_data(build_setting: &BuildSetting, table_data: &TableData) -> String {
+    match table_data.gable_type {
+        ESheetType::Normal | ESheetType::Localize => {
+            normal_xml_data(table_data, build_setting.xml_layout)
+        }
+        ESheetType::KV => kv_xml_data(table_data, build_setting.xml_layout),
         _ => {
             log::error!("The enumeration table does not export as XML.");
             String::new()
@@ -83,224 +75,166 @@ fn to_xml_data(tree_data: &TreeData, keyword: &str) -> String {
 }
 
 /**
- * 普通表格转换为XML
- * @param tree_data 树数据
- * @param keyword 关键字
+ * 普通表格和本地化表格转换为XML，每行数据为一个item节点
+ * @param table_data 表数据
+ * @param layout 数据布局
 */
-fn normal_xml_data(tree_data: &TreeData, keyword: &str) -> String {
-    let (valids_main, valids) = tree_data.content.get_valid_normal_heads(keyword);
-    if valids_main.is_empty() || valids.is_empty() {
-        return String::new();
-    }
+fn normal_xml_data(table_data: &TableData, layout: EXmlLayoutType) -> String {
     let mut xml_content: String = String::new();
 
     // XML头部
     xml_content.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    xml_content.push_str(&format!("<{}>\n", tree_data.file_name));
-
-    let max_row: usize = tree_data.content.get_max_row() + 1;
-    for row_index in constant::TABLE_NORMAL_ROW_TOTAL..=max_row {
-        let real_index: usize = row_index - constant::TABLE_NORMAL_ROW_TOTAL;
-        let row_data = if let Some(row_data) = tree_data.content.cells.get(real_index) {
-            row_data
-        } else {
-            continue;
-        };
-
-        let mut row_valid: bool = true;
-        // 检测行数据是否有效，主键没有数据，行数据无效则跳过
-        for (col_index, _) in valids_main.iter() {
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                row_valid = false;
-                continue;
-            };
-            if value_cell.value.is_empty() {
-                row_valid = false;
-                continue;
-            };
-        }
-        // 行数据无效
-        if !row_valid {
-            continue;
-        }
-
-        xml_content.push_str("  <item>\n");
-
-        // 主键字段
-        for (col_index, col_data) in valids_main.iter() {
-            let field_cell: &CellData = col_data.get(&constant::TABLE_NORMAL_ROW_FIELD).unwrap();
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                continue;
-            };
-            let field_name: String = field_cell.value.replace("*", "");
-            let value: String = escape_xml_value(&value_cell.value);
-            xml_content.push_str(&format!("    <{}>{}</{}>\n", field_name, value, field_name));
-        }
-
-        // 其他字段
-        for (col_index, col_data) in valids.iter() {
-            let field_cell: &CellData = col_data.get(&constant::TABLE_NORMAL_ROW_FIELD).unwrap();
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                continue;
-            };
-            let field_name: &String = &field_cell.value;
-            let value: String = escape_xml_value(&value_cell.value);
-            xml_content.push_str(&format!("    <{}>{}</{}>\n", field_name, value, field_name));
-        }
-
-        xml_content.push_str("  </item>\n");
+    xml_content.push_str(&format!("<{}>\n", table_data.file_name));
+    for row_data in table_data.rows.iter() {
+        write_row(&mut xml_content, "item", table_data, row_data, "  ", layout);
     }
-
-    xml_content.push_str(&format!("</{}>\n", tree_data.file_name));
+    xml_content.push_str(&format!("</{}>\n", table_data.file_name));
     xml_content
 }
 
 /**
- * KV表格转换为XML
- * @param tree_data 树数据
- * @param keyword 关键字
+ * KV表格转换为XML，根节点即为唯一的一行数据
+ * @param table_data 表数据
+ * @param layout 数据布局
 */
-fn kv_xml_data(tree_data: &TreeData, keyword: &str) -> String {
+fn kv_xml_data(table_data: &TableData, layout: EXmlLayoutType) -> String {
     let mut xml_content: String = String::new();
 
     // XML头部
     xml_content.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    xml_content.push_str(&format!("<{}>\n", tree_data.file_name));
-
-    for row_data in tree_data.content.cells.iter() {
-        let field_cell: &CellData =
-            if let Some(field_cell) = row_data.get(constant::TABLE_KV_COL_FIELD) {
-                field_cell
-            } else {
-                continue;
-            };
-        let type_cell: &CellData =
-            if let Some(type_cell) = row_data.get(constant::TABLE_KV_COL_TYPE) {
-                type_cell
-            } else {
-                continue;
-            };
-        let keyword_cell: &CellData =
-            if let Some(keyword_cell) = row_data.get(constant::TABLE_KV_COL_KEYWORD) {
-                keyword_cell
-            } else {
-                continue;
-            };
-        let value_cell: &CellData =
-            if let Some(value_cell) = row_data.get(constant::TABLE_KV_COL_VALUE) {
-                value_cell
-            } else {
-                continue;
-            };
-
-        // 验证字段是否合法
-        if !field_cell.verify_lawful() {
-            continue;
-        }
-        // 验证数据类型是否合法
-        if !type_cell.verify_lawful() {
-            continue;
-        }
-        // 验证keyword是否合法
-        if !keyword_cell.verify_lawful() {
-            continue;
-        }
-        if !keyword_cell.value.contains(keyword) {
-            continue;
-        }
-
-        let field_name: &String = &field_cell.value;
-        let value: String = escape_xml_value(&value_cell.value);
-        xml_content.push_str(&format!("  <{}>{}</{}>\n", field_name, value, field_name));
-    }
-
-    xml_content.push_str(&format!("</{}>\n", tree_data.file_name));
+    let empty_row: Map<String, Value> = Map::new();
+    let row_data: &Map<String, Value> = table_data.rows.first().unwrap_or(&empty_row);
+    write_row(
+        &mut xml_content,
+        &table_data.file_name,
+        table_data,
+        row_data,
+        "",
+        layout,
+    );
     xml_content
 }
 
 /**
- * 本地化表格转换为XML
- * @param tree_data 树数据
- * @param keyword 关键字
+ * 写入一行数据，按字段顺序写入，没有值的字段不写入
+ * Elements布局下每个字段为一个子节点；Attributes布局下标量字段为属性，向量和数组为子节点
+ * @param xml_content XML内容
+ * @param tag 节点名
+ * @param table_data 表数据
+ * @param row_data 行数据
+ * @param indent 缩进
+ * @param layout 数据布局
 */
-fn localize_xml_data(tree_data: &TreeData, keyword: &str) -> String {
-    let (valids_main, valids) = tree_data.content.get_valid_normal_heads(keyword);
-    if valids_main.is_empty() || valids.is_empty() {
-        return String::new();
-    }
-    let mut xml_content: String = String::new();
-
-    // XML头部
-    xml_content.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    xml_content.push_str(&format!("<{}>\n", tree_data.file_name));
-
-    let max_row: usize = tree_data.content.get_max_row() + 1;
-    for row_index in constant::TABLE_LOCALIZE_ROW_TOTAL..=max_row {
-        let real_index: usize = row_index - constant::TABLE_LOCALIZE_ROW_TOTAL;
-        let row_data = if let Some(row_data) = tree_data.content.cells.get(real_index) {
-            row_data
-        } else {
-            continue;
+fn write_row(
+    xml_content: &mut String,
+    tag: &str,
+    table_data: &TableData,
+    row_data: &Map<String, Value>,
+    indent: &str,
+    layout: EXmlLayoutType,
+) {
+    let child_indent: String = format!("{}  ", indent);
+    let mut attributes: String = String::new();
+    let mut children: String = String::new();
+    for field in table_data.field_info.fields.iter() {
+        let value: &Value = match row_data.get(&field.field_name) {
+            Some(value) => value,
+            None => continue,
         };
+        if layout == EXmlLayoutType::Attributes && !is_structured(value) {
+            attributes.push_str(&format!(
+                " {}=\"{}\"",
+                field.field_name,
+                escape_xml_value(&table_data::to_text(value))
+            ));
+        } else {
+            write_element(
+                &mut children,
+                &field.field_name,
+                value,
+                &child_indent,
+                layout,
+            );
+        }
+    }
+    if children.is_empty() && layout == EXmlLayoutType::Attributes {
+        xml_content.push_str(&format!("{}<{}{}/>\n", indent, tag, attributes));
+    } else {
+        xml_content.push_str(&format!("{}<{}{}>\n", indent, tag, attributes));
+        xml_content.push_str(&children);
+        xml_content.push_str(&format!("{}</{}>\n", indent, tag));
+    }
+}
 
-        let mut row_valid: bool = true;
-        // 检测行数据是否有效，主键没有数据，行数据无效则跳过
-        for (col_index, _) in valids_main.iter() {
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                row_valid = false;
-                continue;
-            };
-            if value_cell.value.is_empty() {
-                row_valid = false;
-                continue;
-            };
+/**
+ * 写入一个字段节点
+ * 数组的每个元素为一个item子节点；向量在Elements布局下每个分量为一个子节点，在Attributes布局下分量为属性
+ * @param xml_content XML内容
+ * @param name 节点名
+ * @param value 值
+ * @param indent 缩进
+ * @param layout 数据布局
+*/
+fn write_element(
+    xml_content: &mut String,
+    name: &str,
+    value: &Value,
+    indent: &str,
+    layout: EXmlLayoutType,
+) {
+    match value {
+        Value::Array(items) if items.is_empty() => {
+            xml_content.push_str(&format!("{}<{}/>\n", indent, name));
         }
-        // 行数据无效
-        if !row_valid {
-            continue;
+        Value::Object(components) if components.is_empty() => {
+            xml_content.push_str(&format!("{}<{}/>\n", indent, name));
         }
-
-        xml_content.push_str("  <item>\n");
-
-        // 主键字段
-        for (col_index, col_data) in valids_main.iter() {
-            let field_cell: &CellData = col_data.get(&constant::TABLE_LOCALIZE_ROW_FIELD).unwrap();
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                continue;
-            };
-            let field_name = field_cell.value.replace("*", "");
-            let value = escape_xml_value(&value_cell.value);
-            xml_content.push_str(&format!("    <{}>{}</{}>\n", field_name, value, field_name));
+        Value::Array(items) => {
+            let child_indent: String = format!("{}  ", indent);
+            xml_content.push_str(&format!("{}<{}>\n", indent, name));
+            for item in items.iter() {
+                write_element(xml_content, "item", item, &child_indent, layout);
+            }
+            xml_content.push_str(&format!("{}</{}>\n", indent, name));
         }
-
-        // 其他字段
-        for (col_index, col_data) in valids.iter() {
-            let field_cell: &CellData = col_data.get(&constant::TABLE_LOCALIZE_ROW_FIELD).unwrap();
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                continue;
-            };
-            let field_name: &String = &field_cell.value;
-            let value: String = escape_xml_value(&value_cell.value);
-            xml_content.push_str(&format!("    <{}>{}</{}>\n", field_name, value, field_name));
+        Value::Object(components) if layout == EXmlLayoutType::Attributes => {
+            let attributes: Vec<String> = components
+                .iter()
+                .map(|(key, value)| {
+                    format!(
+                        "{}=\"{}\"",
+                        key,
+                        escape_xml_value(&table_data::to_text(value))
+                    )
+                })
+                .collect();
+            xml_content.push_str(&format!("{}<{} {}/>\n", indent, name, attributes.join(" ")));
+        }
+        Value::Object(components) => {
+            let child_indent: String = format!("{}  ", indent);
+            xml_content.push_str(&format!("{}<{}>\n", indent, name));
+            for (key, value) in components.iter() {
+                write_element(xml_content, key, value, &child_indent, layout);
+            }
+            xml_content.push_str(&format!("{}</{}>\n", indent, name));
+        }
+        _ => {
+            xml_content.push_str(&format!(
+                "{}<{}>{}</{}>\n",
+                indent,
+                name,
+                escape_xml_value(&table_data::to_text(value)),
+                name
+            ));
         }
-
-        xml_content.push_str("  </item>\n");
     }
+}
 
-    xml_content.push_str(&format!("</{}>\n", tree_data.file_name));
-    xml_content
+/**
+ * 是否为结构化的值（向量或数组），这类值总是写为子节点
+ */
+fn is_structured(value: &Value) -> bool {
+    matches!(value, Value::Array(_) | Value::Object(_))
 }
 
 /**
@@ -308,7 +242,7 @@ fn localize_xml_data(tree_data: &TreeData, keyword: &str) -> String {
  * @param value 原始值
  * @return 转义后的值
 */
-fn escape_xml_value(value: &str) -> String {
+pub fn escape_xml_value(value: &str) -> String {
     value
         .replace("&", "&amp;")
         .replace("<", "&lt;")
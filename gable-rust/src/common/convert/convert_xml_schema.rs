@@ -0,0 +1,325 @@
+use crate::{
+    common::{convert::convert_xml, output, setting::BuildSetting},
+    gui::datas::{
+        edata_type::EDataType, esheet_type::ESheetType, exml_layout_type::EXmlLayoutType, gables,
+        table_data::TableData, tree_data::FieldItem,
+    },
+};
+use std::{io::Error, path::PathBuf};
+
+/**
+ * 生成与xml导出数据对应的XSD，文件名为`表名.xsd`
+ * 结构与xml导出一致：普通表的根节点下每行为一个item节点，KV表的根节点即为数据；字段按xml_layout写为子节点或属性
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ */
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
+        // 枚举不导出
+        return;
+    }
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.xsd", table_data.file_name));
+    let layout: EXmlLayoutType = build_setting.xml_layout;
+
+    let mut xsd_content: String = String::new();
+    xsd_content.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xsd_content.push_str("<xs:schema xmlns:xs=\"http://www.w3.org/2001/XMLSchema\">\n");
+    xsd_content.push_str(&format!(
+        "  <xs:element name=\"{}\">\n",
+        table_data.file_name
+    ));
+    if table_data.gable_type == ESheetType::KV {
+        // KV表的值为空时不会导出，所以没有必填字段
+        write_row_type(
+            &mut xsd_content,
+            &table_data.field_info.fields,
+            &[],
+            "    ",
+            layout,
+        );
+    } else {
+        xsd_content.push_str("    <xs:complexType>\n");
+        xsd_content.push_str("      <xs:sequence>\n");
+        xsd_content.push_str(
+            "        <xs:element name=\"item\" minOccurs=\"0\" maxOccurs=\"unbounded\">\n",
+        );
+        write_row_type(
+            &mut xsd_content,
+            &table_data.field_info.fields,
+            &table_data.primary_keys(),
+            "          ",
+            layout,
+        );
+        xsd_content.push_str("        </xs:element>\n");
+        xsd_content.push_str("      </xs:sequence>\n");
+        xsd_content.push_str("    </xs:complexType>\n");
+    }
+    xsd_content.push_str("  </xs:element>\n");
+    xsd_content.push_str("</xs:schema>\n");
+
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, xsd_content);
+    if result.is_err() {
+        log::error!(
+            "Export [{}] failed: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            target_path.to_str().unwrap()
+        );
+    }
+}
+
+/**
+ * 写入一行数据的类型，主键为必填字段
+ * Elements布局下字段按顺序为子节点；Attributes布局下标量字段为属性，向量和数组为子节点
+ * @param xsd_content XSD内容
+ * @param fields 字段列表
+ * @param primary_keys 主键字段名
+ * @param indent 缩进
+ * @param layout 数据布局
+ */
+fn write_row_type(
+    xsd_content: &mut String,
+    fields: &[FieldItem],
+    primary_keys: &[&str],
+    indent: &str,
+    layout: EXmlLayoutType,
+) {
+    let attribute_indent: String = format!("{}  ", indent);
+    let element_indent: String = format!("{}    ", indent);
+    let mut elements: String = String::new();
+    let mut attributes: String = String::new();
+    for field in fields.iter() {
+        let required: bool = primary_keys.contains(&field.field_name.as_str());
+        let is_scalar: bool =
+            get_simple_type(&field.field_type).is_some() || field.field_type == EDataType::Enum;
+        if layout == EXmlLayoutType::Attributes && is_scalar {
+            let head: String = format!(
+                "xs:attribute name=\"{}\" use=\"{}\"",
+                field.field_name,
+                if required { "required" } else { "optional" }
+            );
+            write_field(&mut attributes, &head, field, &attribute_indent, layout);
+        } else {
+            let head: String = if required {
+                format!("xs:element name=\"{}\"", field.field_name)
+            } else {
+                format!("xs:element name=\"{}\" minOccurs=\"0\"", field.field_name)
+            };
+            write_field(&mut elements, &head, field, &element_indent, layout);
+        }
+    }
+    xsd_content.push_str(&format!("{}<xs:complexType>\n", indent));
+    if !elements.is_empty() {
+        xsd_content.push_str(&format!("{}  <xs:sequence>\n", indent));
+        xsd_content.push_str(&elements);
+        xsd_content.push_str(&format!("{}  </xs:sequence>\n", indent));
+    }
+    xsd_content.push_str(&attributes);
+    xsd_content.push_str(&format!("{}</xs:complexType>\n", indent));
+}
+
+/**
+ * 写入一个字段的节点或属性声明，描述取自表头的描述行
+ * @param xsd_content XSD内容
+ * @param head 声明的标签和属性，如`xs:element name="id"`
+ * @param field 字段
+ * @param indent 缩进
+ * @param layout 数据布局
+ */
+fn write_field(
+    xsd_content: &mut String,
+    head: &str,
+    field: &FieldItem,
+    indent: &str,
+    layout: EXmlLayoutType,
+) {
+    let tag: &str = head.split(' ').next().unwrap_or(head);
+    let simple_type: Option<&str> = get_simple_type(&field.field_type);
+    let inner_indent: String = format!("{}  ", indent);
+    match simple_type {
+        Some(simple_type) if field.field_desc.is_empty() => {
+            xsd_content.push_str(&format!("{}<{} type=\"{}\"/>\n", indent, head, simple_type));
+            return;
+        }
+        Some(simple_type) => {
+            xsd_content.push_str(&format!("{}<{} type=\"{}\">\n", indent, head, simple_type));
+            write_annotation(xsd_content, &field.field_desc, &inner_indent);
+        }
+        None => {
+            xsd_content.push_str(&format!("{}<{}>\n", indent, head));
+            if !field.field_desc.is_empty() {
+                write_annotation(xsd_content, &field.field_desc, &inner_indent);
+            }
+            if field.field_type == EDataType::Enum {
+                write_enum_type(xsd_content, &field.field_link, &inner_indent);
+            } else {
+                write_structured_type(xsd_content, &field.field_type, &inner_indent, layout);
+            }
+        }
+    }
+    xsd_content.push_str(&format!("{}</{}>\n", indent, tag));
+}
+
+/**
+ * 写入向量或数组的类型，数组的每个元素为一个item子节点
+ * @param xsd_content XSD内容
+ * @param data_type 数据类型
+ * @param indent 缩进
+ * @param layout 数据布局
+ */
+fn write_structured_type(
+    xsd_content: &mut String,
+    data_type: &EDataType,
+    indent: &str,
+    layout: EXmlLayoutType,
+) {
+    xsd_content.push_str(&format!("{}<xs:complexType>\n", indent));
+    if let Some(item_type) = get_array_item_type(data_type) {
+        xsd_content.push_str(&format!("{}  <xs:sequence>\n", indent));
+        let head: &str = "xs:element name=\"item\" minOccurs=\"0\" maxOccurs=\"unbounded\"";
+        match get_simple_type(&item_type) {
+            Some(simple_type) => {
+                xsd_content.push_str(&format!(
+                    "{}    <{} type=\"{}\"/>\n",
+                    indent, head, simple_type
+                ));
+            }
+            None => {
+                xsd_content.push_str(&format!("{}    <{}>\n", indent, head));
+                write_structured_type(
+                    xsd_content,
+                    &item_type,
+                    &format!("{}      ", indent),
+                    layout,
+                );
+                xsd_content.push_str(&format!("{}    </xs:element>\n", indent));
+            }
+        }
+        xsd_content.push_str(&format!("{}  </xs:sequence>\n", indent));
+    } else {
+        let components: &[&str] = get_vector_components(data_type);
+        if layout == EXmlLayoutType::Attributes {
+            for component in components.iter() {
+                xsd_content.push_str(&format!(
+                    "{}  <xs:attribute name=\"{}\" type=\"xs:double\" use=\"required\"/>\n",
+                    indent, component
+                ));
+            }
+        } else {
+            xsd_content.push_str(&format!("{}  <xs:sequence>\n", indent));
+            for component in components.iter() {
+                xsd_content.push_str(&format!(
+                    "{}    <xs:element name=\"{}\" type=\"xs:double\"/>\n",
+                    indent, component
+                ));
+            }
+            xsd_content.push_str(&format!("{}  </xs:sequence>\n", indent));
+        }
+    }
+    xsd_content.push_str(&format!("{}</xs:complexType>\n", indent));
+}
+
+/**
+ * 写入枚举类型，取值范围来自关联的枚举表，枚举表不存在时只限定为整数
+ * @param xsd_content XSD内容
+ * @param link_name 关联的枚举名
+ * @param indent 缩进
+ */
+fn write_enum_type(xsd_content: &mut String, link_name: &str, indent: &str) {
+    xsd_content.push_str(&format!("{}<xs:simpleType>\n", indent));
+    match gables::get_enum_members(link_name) {
+        Some(members) if !members.is_empty() => {
+            xsd_content.push_str(&format!("{}  <xs:restriction base=\"xs:int\">\n", indent));
+            for (value, name) in members.iter() {
+                xsd_content.push_str(&format!(
+                    "{}    <xs:enumeration value=\"{}\">\n",
+                    indent, value
+                ));
+                write_annotation(xsd_content, name, &format!("{}      ", indent));
+                xsd_content.push_str(&format!("{}    </xs:enumeration>\n", indent));
+            }
+            xsd_content.push_str(&format!("{}  </xs:restriction>\n", indent));
+        }
+        _ => {
+            log::warn!(
+                "Enum {} not found, schema only checks for an integer",
+                link_name
+            );
+            xsd_content.push_str(&format!("{}  <xs:restriction base=\"xs:int\"/>\n", indent));
+        }
+    }
+    xsd_content.push_str(&format!("{}</xs:simpleType>\n", indent));
+}
+
+/**
+ * 写入说明
+ * @param xsd_content XSD内容
+ * @param documentation 说明文本
+ * @param indent 缩进
+ */
+fn write_annotation(xsd_content: &mut String, documentation: &str, indent: &str) {
+    xsd_content.push_str(&format!("{}<xs:annotation>\n", indent));
+    xsd_content.push_str(&format!(
+        "{}  <xs:documentation>{}</xs:documentation>\n",
+        indent,
+        convert_xml::escape_xml_value(documentation)
+    ));
+    xsd_content.push_str(&format!("{}</xs:annotation>\n", indent));
+}
+
+/**
+ * 标量对应的XSD内置类型，枚举、向量和数组返回None
+ * @param data_type 数据类型
+ */
+fn get_simple_type(data_type: &EDataType) -> Option<&'static str> {
+    match data_type {
+        EDataType::Int => Some("xs:int"),
+        EDataType::Long | EDataType::Time | EDataType::Date => Some("xs:long"),
+        EDataType::Boolean => Some("xs:boolean"),
+        EDataType::Float | EDataType::Percentage | EDataType::Permillage | EDataType::Permian => {
+            Some("xs:double")
+        }
+        EDataType::Unknown | EDataType::String | EDataType::Loc => Some("xs:string"),
+        _ => None,
+    }
+}
+
+/**
+ * 数组元素的数据类型，不是数组时返回None
+ * @param data_type 数据类型
+ */
+fn get_array_item_type(data_type: &EDataType) -> Option<EDataType> {
+    match data_type {
+        EDataType::IntArr => Some(EDataType::Int),
+        EDataType::LongArr => Some(EDataType::Long),
+        EDataType::StringArr => Some(EDataType::String),
+        EDataType::BooleanArr => Some(EDataType::Boolean),
+        EDataType::FloatArr => Some(EDataType::Float),
+        EDataType::Vector2Arr => Some(EDataType::Vector2),
+        EDataType::Vector3Arr => Some(EDataType::Vector3),
+        EDataType::Vector4Arr => Some(EDataType::Vector4),
+        _ => None,
+    }
+}
+
+/**
+ * 向量的分量
+ * @param data_type 数据类型
+ */
+fn get_vector_components(data_type: &EDataType) -> &'static [&'static str] {
+    match data_type {
+        EDataType::Vector2 => &["x", "y"],
+        EDataType::Vector3 => &["x", "y", "z"],
+        _ => &["x", "y", "z", "w"],
+    }
+}
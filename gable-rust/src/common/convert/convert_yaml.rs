@@ -1,41 +1,42 @@
 use crate::{
-    common::{constant, setting::BuildSetting, utils},
-    gui::datas::{cell_data::CellData, esheet_type::ESheetType, tree_data::TreeData},
-};
-use serde_json::Value as JsonValue;
-use std::collections::BTreeMap;
-use std::{
-    fs::File,
-    io::{BufWriter, Error, Write},
-    path::PathBuf,
+    common::{convert::convert_toml, output, setting::BuildSetting},
+    gui::datas::{
+        esheet_type::ESheetType, eyaml_style_type::EYamlStyleType, table_data::TableData,
+    },
 };
+use serde_json::{Map, Value};
+use std::{collections::HashMap, path::PathBuf};
 
 /**
  * yaml 转换
  * @param build_setting 构建设置
- * @param tree_data 树数据
+ * @param table_data 表数据
  * */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    if tree_data.gable_type == ESheetType::Enum {
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    if table_data.gable_type == ESheetType::Enum {
         // 枚举不导出
         return;
     }
 
-    let target_path: PathBuf = utils::get_absolute_path(&build_setting.target_path)
-        .join(format!("{}.yaml", tree_data.file_name));
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.target_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.yaml", table_data.file_name));
 
-    let yaml_data: String = to_yaml_data(tree_data, &build_setting.keyword);
+    let yaml_data: String = to_yaml_data(build_setting, table_data);
     if yaml_data.is_empty() {
         log::debug!(
             "Export [{}] skipped: {}",
             build_setting.display_name,
             target_path.to_str().unwrap()
         );
+        output::remove(build_setting, &target_path);
         return;
     }
-    // 创建YAML文件
-    let file: Result<File, Error> = File::create(&target_path);
-    if file.is_err() {
+    // 写入YAML文件
+    if output::write(build_setting, &target_path, yaml_data).is_err() {
         log::error!(
             "Export [{}] failed: {}",
             build_setting.display_name,
@@ -43,23 +44,6 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
         );
         return;
     }
-    let file: File = file.unwrap();
-    let mut writer: BufWriter<File> = BufWriter::new(file);
-
-    // 写入YAML数据
-    if let Err(e) = writer.write_all(yaml_data.as_bytes()) {
-        log::error!("Error writing [{}] file: {}", build_setting.display_name, e);
-        return;
-    }
-
-    if let Err(e) = writer.flush() {
-        log::error!(
-            "Error flushing [{}] file: {}",
-            build_setting.display_name,
-            e
-        );
-        return;
-    }
 
     log::info!(
         "Export [{}] successful: {}",
@@ -69,235 +53,123 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
 }
 
 /**
- *  转换为yaml数据
- *  @param tree_data 树数据
- *  @param keyword 关键字
+ *  转换为yaml数据，以表名为根节点：普通表和本地化表为行数据的列表，KV表为键值对
+ *  普通表的字段描述写在文件开头，KV表的字段描述写在对应的键上方
+ *  @param build_setting 构建设置
+ *  @param table_data 表数据
  * */
-fn to_yaml_data(tree_data: &TreeData, keyword: &str) -> String {
-    match tree_data.gable_type {
-        ESheetType::Normal => normal_yaml_data(tree_data, keyword),
-        ESheetType::KV => kv_yaml_data(tree_data, keyword),
-        ESheetType::Localize => localize_yaml_data(tree_data, keyword),
+fn to_yaml_data(build_setting: &BuildSetting, table_data: &TableData) -> String {
+    let descs: HashMap<&str, &str> = if build_setting.yaml_comments {
+        convert_toml::get_field_descs(table_data)
+    } else {
+        HashMap::new()
+    };
+    let mut contents: String = String::new();
+    match table_data.gable_type {
+        ESheetType::Normal | ESheetType::Localize => {
+            if table_data.rows.is_empty() {
+                return String::new();
+            }
+            for field in table_data.field_info.fields.iter() {
+                if let Some(desc) = descs.get(field.field_name.as_str()) {
+                    contents.push_str(&format!(
+                        "# {}: {}\n",
+                        field.field_name,
+                        convert_toml::single_line(desc)
+                    ));
+                }
+            }
+            contents.push_str(&format!("{}:\n", to_key(&table_data.file_name)));
+            for row_data in table_data.rows.iter() {
+                match build_setting.yaml_style {
+                    EYamlStyleType::Block => {
+                        let block: String = to_block(&Value::Object(row_data.clone()));
+                        for (index, line) in block.lines().enumerate() {
+                            contents.push_str(if index == 0 { "- " } else { "  " });
+                            contents.push_str(line);
+                            contents.push('\n');
+                        }
+                    }
+                    EYamlStyleType::Flow => {
+                        contents.push_str(&format!(
+                            "- {}\n",
+                            to_flow(&Value::Object(row_data.clone()))
+                        ));
+                    }
+                }
+            }
+        }
+        ESheetType::KV => {
+            contents.push_str(&format!("{}:\n", to_key(&table_data.file_name)));
+            let row_data: Map<String, Value> = table_data.rows.first().cloned().unwrap_or_default();
+            for (field_name, value) in row_data.into_iter() {
+                if let Some(desc) = descs.get(field_name.as_str()) {
+                    contents.push_str(&format!("  # {}\n", convert_toml::single_line(desc)));
+                }
+                match build_setting.yaml_style {
+                    EYamlStyleType::Block => {
+                        let mut item: Map<String, Value> = Map::new();
+                        item.insert(field_name, value);
+                        for line in to_block(&Value::Object(item)).lines() {
+                            contents.push_str("  ");
+                            contents.push_str(line);
+                            contents.push('\n');
+                        }
+                    }
+                    EYamlStyleType::Flow => {
+                        contents.push_str(&format!(
+                            "  {}: {}\n",
+                            to_key(&field_name),
+                            to_flow(&value)
+                        ));
+                    }
+                }
+            }
+        }
         _ => {
             log::error!("The enumeration table does not export as YAML.");
-            String::new()
         }
     }
+    contents
 }
 
 /**
- * 普通表格转换为YAML
- * @param tree_data 树数据
- * @param keyword 关键字
-*/
-fn normal_yaml_data(tree_data: &TreeData, keyword: &str) -> String {
-    let (valids_main, valids) = tree_data.content.get_valid_normal_heads(keyword);
-    if valids_main.is_empty() || valids.is_empty() {
-        return String::new();
-    }
-    let mut yaml_root: BTreeMap<String, JsonValue> = BTreeMap::new();
-    let mut rows_data: Vec<BTreeMap<String, String>> = Vec::new();
-
-    let max_row: usize = tree_data.content.get_max_row() + 1;
-    for row_index in constant::TABLE_NORMAL_ROW_TOTAL..=max_row {
-        let real_index: usize = row_index - constant::TABLE_NORMAL_ROW_TOTAL;
-        let row_data: &Vec<CellData> =
-            if let Some(row_data) = tree_data.content.cells.get(real_index) {
-                row_data
-            } else {
-                continue;
-            };
-
-        let mut row_valid: bool = true;
-        let mut row_item: BTreeMap<String, String> = BTreeMap::new();
-
-        // 检测行数据是否有效，主键没有数据，行数据无效则跳过
-        for (col_index, col_data) in valids_main.iter() {
-            let field_cell: &CellData = col_data.get(&constant::TABLE_NORMAL_ROW_FIELD).unwrap();
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                row_valid = false;
-                break;
-            };
-            if value_cell.value.is_empty() {
-                row_valid = false;
-                break;
-            };
-            let field_name: String = field_cell.value.replace("*", "");
-            row_item.insert(field_name, value_cell.value.clone());
-        }
-        // 行数据无效
-        if !row_valid {
-            continue;
-        }
-
-        // 其他字段
-        for (col_index, col_data) in valids.iter() {
-            let field_cell: &CellData = col_data.get(&constant::TABLE_NORMAL_ROW_FIELD).unwrap();
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                continue;
-            };
-            row_item.insert(field_cell.value.clone(), value_cell.value.clone());
-        }
-
-        rows_data.push(row_item);
-    }
-
-    yaml_root.insert(
-        tree_data.file_name.clone(),
-        serde_json::Value::Array(
-            rows_data
-                .into_iter()
-                .map(|map| {
-                    serde_json::Value::Object(
-                        map.into_iter()
-                            .map(|(k, v)| (k, serde_json::Value::String(v)))
-                            .collect(),
-                    )
-                })
-                .collect(),
-        ),
-    );
-
-    serde_yaml::to_string(&yaml_root).unwrap_or_else(|_| String::from("{}\n"))
+ * 块风格，由serde_yaml负责转义和类型
+ */
+fn to_block(value: &Value) -> String {
+    serde_yaml::to_string(value).unwrap_or_else(|_| String::from("{}\n"))
 }
 
 /**
- * KV表格转换为YAML
- * @param tree_data 树数据
- * @param keyword 关键字
-*/
-fn kv_yaml_data(tree_data: &TreeData, keyword: &str) -> String {
-    let mut yaml_data: BTreeMap<String, String> = BTreeMap::new();
-
-    for row_data in tree_data.content.cells.iter() {
-        let field_cell: &CellData =
-            if let Some(field_cell) = row_data.get(constant::TABLE_KV_COL_FIELD) {
-                field_cell
-            } else {
-                continue;
-            };
-        let keyword_cell: &CellData =
-            if let Some(keyword_cell) = row_data.get(constant::TABLE_KV_COL_KEYWORD) {
-                keyword_cell
-            } else {
-                continue;
-            };
-        let value_cell: &CellData =
-            if let Some(value_cell) = row_data.get(constant::TABLE_KV_COL_VALUE) {
-                value_cell
-            } else {
-                continue;
-            };
-
-        // 验证字段是否合法
-        if !field_cell.verify_lawful() {
-            continue;
-        }
-        // 验证keyword是否合法
-        if !keyword_cell.verify_lawful() {
-            continue;
-        }
-        if !keyword_cell.value.contains(keyword) {
-            continue;
-        }
-
-        yaml_data.insert(field_cell.value.clone(), value_cell.value.clone());
+ * 键名，需要时由serde_yaml加引号；包含控制字符时使用双引号
+ */
+fn to_key(name: &str) -> String {
+    if name.chars().any(|c| c.is_control()) {
+        return convert_toml::to_quoted(name);
+    }
+    match serde_yaml::to_string(name) {
+        Ok(key) => key.trim_end().to_string(),
+        Err(_) => convert_toml::to_quoted(name),
     }
-
-    let mut root_data: BTreeMap<String, JsonValue> = BTreeMap::new();
-    root_data.insert(
-        tree_data.file_name.clone(),
-        serde_json::Value::Object(
-            yaml_data
-                .into_iter()
-                .map(|(k, v)| (k, serde_json::Value::String(v)))
-                .collect(),
-        ),
-    );
-
-    serde_yaml::to_string(&root_data).unwrap_or_else(|_| String::from("{}\n"))
 }
 
 /**
- * 本地化表格转换为YAML
- * @param tree_data 树数据
- * @param keyword 关键字
-*/
-fn localize_yaml_data(tree_data: &TreeData, keyword: &str) -> String {
-    let (valids_main, valids) = tree_data.content.get_valid_normal_heads(keyword);
-    if valids_main.is_empty() || valids.is_empty() {
-        return String::new();
-    }
-    let mut yaml_root: BTreeMap<String, JsonValue> = BTreeMap::new();
-    let mut rows_data: Vec<BTreeMap<String, String>> = Vec::new();
-
-    let max_row: usize = tree_data.content.get_max_row() + 1;
-    for row_index in constant::TABLE_LOCALIZE_ROW_TOTAL..=max_row {
-        let real_index: usize = row_index - constant::TABLE_LOCALIZE_ROW_TOTAL;
-        let row_data: &Vec<CellData> =
-            if let Some(row_data) = tree_data.content.cells.get(real_index) {
-                row_data
-            } else {
-                continue;
-            };
-
-        let mut row_valid: bool = true;
-        let mut row_item: BTreeMap<String, String> = BTreeMap::new();
-
-        // 检测行数据是否有效，主键没有数据，行数据无效则跳过
-        for (col_index, col_data) in valids_main.iter() {
-            let field_cell: &CellData = col_data.get(&constant::TABLE_LOCALIZE_ROW_FIELD).unwrap();
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                row_valid = false;
-                break;
-            };
-            if value_cell.value.is_empty() {
-                row_valid = false;
-                break;
-            };
-            let field_name = field_cell.value.replace("*", "");
-            row_item.insert(field_name, value_cell.value.clone());
+ * 流风格，字符串使用双引号（json的字符串在yaml中同样合法）
+ */
+fn to_flow(value: &Value) -> String {
+    match value {
+        Value::Null | Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::String(text) => convert_toml::to_quoted(text),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(to_flow).collect();
+            format!("[{}]", items.join(", "))
         }
-        // 行数据无效
-        if !row_valid {
-            continue;
+        Value::Object(object) => {
+            let items: Vec<String> = object
+                .iter()
+                .map(|(key, item)| format!("{}: {}", to_key(key), to_flow(item)))
+                .collect();
+            format!("{{{}}}", items.join(", "))
         }
-
-        // 其他字段
-        for (col_index, col_data) in valids.iter() {
-            let field_cell: &CellData = col_data.get(&constant::TABLE_LOCALIZE_ROW_FIELD).unwrap();
-            let value_cell: &CellData = if let Some(value_cell) = row_data.get(*col_index) {
-                value_cell
-            } else {
-                continue;
-            };
-            row_item.insert(field_cell.value.clone(), value_cell.value.clone());
-        }
-
-        rows_data.push(row_item);
     }
-
-    yaml_root.insert(
-        tree_data.file_name.clone(),
-        serde_json::Value::Array(
-            rows_data
-                .into_iter()
-                .map(|map| {
-                    serde_json::Value::Object(
-                        map.into_iter()
-                            .map(|(k, v)| (k, serde_json::Value::String(v)))
-                            .collect(),
-                    )
-                })
-                .collect(),
-        ),
-    );
-
-    serde_yaml::to_string(&yaml_root).unwrap_or_else(|_| String::from("{}\n"))
 }
@@ -0,0 +1,391 @@
+use crate::{
+    common::{
+        constant, convert::convert, generate::proto_field_info, setting::BuildSetting, utils,
+    },
+    gui::datas::{
+        esheet_type::ESheetType, etarget_type::ETargetType, table_data::TableData,
+        tree_data::TreeData,
+    },
+};
+use prost::{
+    bytes::Buf,
+    encoding::{self, WireType},
+};
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, Kind, MessageDescriptor, ReflectMessage, Value as ProtoValue,
+};
+use serde_json::{Map, Value};
+use std::{borrow::Cow, collections::BTreeMap, fs, path::PathBuf};
+
+/**
+ * 校验多个表导出的protobuff二进制数据
+ * @param build_setting 构建设置
+ * @param datas 表名和表数据的映射
+ * @return 不一致的数量
+ */
+pub fn from_tables(build_setting: &BuildSetting, datas: &BTreeMap<String, Cow<TreeData>>) -> usize {
+    if build_setting.target_type != ETargetType::Protobuff {
+        return 0;
+    }
+    let mut mismatches: usize = 0;
+    for (_, data) in datas.iter() {
+        mismatches += execute(build_setting, data);
+    }
+    if build_setting.proto_bundle {
+        mismatches += verify_bundle(build_setting, datas);
+    }
+    if mismatches > 0 {
+        log::error!(
+            "Protobuf verification [{}] failed: {} mismatches",
+            build_setting.display_name,
+            mismatches
+        );
+    } else {
+        log::info!(
+            "Protobuf verification [{}] passed",
+            build_setting.display_name
+        );
+    }
+    mismatches
+}
+
+/**
+ * 使用生成的proto文件解码导出的二进制数据，并与表数据逐字段比较
+ * @param build_setting 构建设置
+ * @param tree_data 树数据
+ * @return 不一致的数量
+ */
+pub fn execute(build_setting: &BuildSetting, tree_data: &TreeData) -> usize {
+    if tree_data.gable_type == ESheetType::Enum {
+        return 0;
+    }
+    let data: Cow<TreeData> = match convert::transform(build_setting, tree_data) {
+        Some(data) => data,
+        None => return 1,
+    };
+    let table_data: TableData = match TableData::new(&data, &build_setting.keyword) {
+        Some(table_data) => table_data,
+        None => return 0,
+    };
+    let values: &[Map<String, Value>] = &table_data.rows;
+    if values.is_empty() {
+        return 0;
+    }
+    let (descriptor, bytes) = match load_message(build_setting, &data.file_name) {
+        Some(loaded) => loaded,
+        None => return 1,
+    };
+    let messages: Vec<DynamicMessage> = match decode(&descriptor, &data.gable_type, &bytes) {
+        Ok(messages) => messages,
+        Err(e) => {
+            log::error!("Failed to decode {}.bytes: {}", data.file_name, e);
+            return 1;
+        }
+    };
+
+    let mut errors: Vec<String> = Vec::new();
+    if messages.len() != values.len() {
+        errors.push(format!("row count {} != {}", messages.len(), values.len()));
+    }
+    for (row_index, (message, value)) in messages.iter().zip(values.iter()).enumerate() {
+        compare_message(&format!("[{}]", row_index), message, value, &mut errors);
+    }
+    for error in errors.iter() {
+        log::error!("Protobuf verification {}: {}", data.file_name, error);
+    }
+    errors.len()
+}
+
+/**
+ * 使用生成的汇总消息解码汇总的二进制数据，并与各表数据逐字段比较
+ * @param build_setting 构建设置
+ * @param datas 表名和表数据的映射
+ * @return 不一致的数量
+ */
+fn verify_bundle(build_setting: &BuildSetting, datas: &BTreeMap<String, Cow<TreeData>>) -> usize {
+    let (_, bundle_info, _) = proto_field_info::transition_bundle(build_setting, datas);
+    // 汇总消息字段名 -> 期望的JSON值，没有数据的表不会写入汇总数据
+    let mut expected: Map<String, Value> = Map::new();
+    for field in bundle_info.fields.iter() {
+        let source: &TreeData = match datas.values().find(|d| d.file_name == field.field_desc) {
+            Some(source) => source,
+            None => continue,
+        };
+        let data: Cow<TreeData> = match convert::transform(build_setting, source) {
+            Some(data) => data,
+            None => continue,
+        };
+        let table_data: TableData = match TableData::new(&data, &build_setting.keyword) {
+            Some(table_data) => table_data,
+            None => continue,
+        };
+        if table_data.rows.is_empty() {
+            continue;
+        }
+        let value: Value = if table_data.gable_type == ESheetType::KV {
+            Value::Object(table_data.rows[0].clone())
+        } else {
+            Value::Array(table_data.rows.into_iter().map(Value::Object).collect())
+        };
+        expected.insert(field.field_name.clone(), value);
+    }
+    let bundle_name: &str = constant::PROTO_BUNDLE_NAME;
+    let (descriptor, bytes) = match load_message(build_setting, bundle_name) {
+        Some(loaded) => loaded,
+        None => return 1,
+    };
+    let message: DynamicMessage = match DynamicMessage::decode(descriptor, bytes.as_slice()) {
+        Ok(message) => message,
+        Err(e) => {
+            log::error!("Failed to decode {}.bytes: {}", bundle_name, e);
+            return 1;
+        }
+    };
+    let mut errors: Vec<String> = Vec::new();
+    compare_message("", &message, &expected, &mut errors);
+    for error in errors.iter() {
+        log::error!("Protobuf verification {}: {}", bundle_name, error);
+    }
+    errors.len()
+}
+
+/**
+ * 编译消息对应的proto文件并读取导出的二进制数据
+ * @param build_setting 构建设置
+ * @param message_name 消息名，同时也是proto文件名和二进制文件名
+ * @return 消息描述和二进制数据，失败时返回None
+ */
+fn load_message(
+    build_setting: &BuildSetting,
+    message_name: &str,
+) -> Option<(MessageDescriptor, Vec<u8>)> {
+    let pool: DescriptorPool = match compile(build_setting, message_name) {
+        Ok(pool) => pool,
+        Err(e) => {
+            log::error!("Failed to compile {}.proto: {}", message_name, e);
+            return None;
+        }
+    };
+    let descriptor: MessageDescriptor = match pool.all_messages().find(|m| m.name() == message_name)
+    {
+        Some(descriptor) => descriptor,
+        None => {
+            log::error!(
+                "Message {} not found in {}.proto",
+                message_name,
+                message_name
+            );
+            return None;
+        }
+    };
+    let bytes_path: PathBuf = utils::get_absolute_path(&build_setting.target_path)
+        .join(format!("{}.bytes", message_name));
+    match fs::read(&bytes_path) {
+        Ok(bytes) => Some((descriptor, bytes)),
+        Err(e) => {
+            log::error!("Failed to read {}: {}", bytes_path.display(), e);
+            None
+        }
+    }
+}
+
+/**
+ * 编译生成目录下的proto文件
+ * @param build_setting 构建设置
+ * @param table_name 表名
+ * @return 描述符池
+ */
+fn compile(
+    build_setting: &BuildSetting,
+    table_name: &str,
+) -> Result<DescriptorPool, protox::Error> {
+    let script_path: PathBuf = utils::get_absolute_path(&build_setting.script_path);
+    let mut compiler: protox::Compiler = protox::Compiler::new([script_path])?;
+    compiler
+        .include_imports(true)
+        .open_file(format!("{}.proto", table_name))?;
+    Ok(compiler.descriptor_pool())
+}
+
+/**
+ * 解码二进制数据，普通表是由字段1重复组成的行列表，KV表是单条消息
+ * @return 解码后的消息列表，数据格式不正确时返回错误信息
+ */
+fn decode(
+    descriptor: &MessageDescriptor,
+    gable_type: &ESheetType,
+    bytes: &[u8],
+) -> Result<Vec<DynamicMessage>, String> {
+    if *gable_type == ESheetType::KV {
+        let message: DynamicMessage =
+            DynamicMessage::decode(descriptor.clone(), bytes).map_err(|e| e.to_string())?;
+        return Ok(vec![message]);
+    }
+    let mut messages: Vec<DynamicMessage> = Vec::new();
+    let mut buf: &[u8] = bytes;
+    while buf.has_remaining() {
+        let (tag, wire_type) = encoding::decode_key(&mut buf).map_err(|e| e.to_string())?;
+        if tag != 1 || wire_type != WireType::LengthDelimited {
+            return Err(format!(
+                "unexpected field {} ({:?}) in row list",
+                tag, wire_type
+            ));
+        }
+        let len: usize = encoding::decode_varint(&mut buf).map_err(|e| e.to_string())? as usize;
+        if len > buf.remaining() {
+            return Err("row length out of range".to_string());
+        }
+        let message: DynamicMessage =
+            DynamicMessage::decode(descriptor.clone(), &buf[..len]).map_err(|e| e.to_string())?;
+        messages.push(message);
+        buf.advance(len);
+    }
+    Ok(messages)
+}
+
+/**
+ * 逐字段比较消息和JSON值，双向检查：表数据中的字段必须解码出相同的值，解码出的字段也必须出现在表数据中
+ * @param path 当前位置，用于输出错误
+ * @param message 解码后的消息
+ * @param value 期望的JSON值
+ * @param errors 错误列表
+ */
+fn compare_message(
+    path: &str,
+    message: &DynamicMessage,
+    value: &Map<String, Value>,
+    errors: &mut Vec<String>,
+) {
+    let descriptor: MessageDescriptor = message.descriptor();
+    for (key, expected) in value.iter() {
+        let field = match descriptor.get_field_by_name(key) {
+            Some(field) => field,
+            None => {
+                errors.push(format!("{}.{} is not defined in the schema", path, key));
+                continue;
+            }
+        };
+        let actual: Cow<ProtoValue> = message.get_field(&field);
+        let field_path: String = format!("{}.{}", path, key);
+        compare_value(&field_path, &field.kind(), expected, &actual, errors);
+    }
+    // 解码出的字段也要出现在表数据中，避免多写的字段被忽略
+    for (field, _) in message.fields() {
+        if !value.contains_key(field.name()) {
+            errors.push(format!(
+                "{}.{} is decoded but missing from the table",
+                path,
+                field.name()
+            ));
+        }
+    }
+    for unknown in message.unknown_fields() {
+        errors.push(format!(
+            "{} has unknown field number {}",
+            path,
+            unknown.number()
+        ));
+    }
+}
+
+/**
+ * 比较单个字段值
+ * @param path 当前位置，用于输出错误
+ * @param kind 字段类型，用于解析枚举名称
+ * @param expected 期望的JSON值
+ * @param actual 解码后的值
+ * @param errors 错误列表
+ */
+fn compare_value(
+    path: &str,
+    kind: &Kind,
+    expected: &Value,
+    actual: &ProtoValue,
+    errors: &mut Vec<String>,
+) {
+    let equal: bool = match actual {
+        ProtoValue::Bool(b) => expected.as_bool() == Some(*b),
+        ProtoValue::I32(n) => expected.as_i64() == Some(*n as i64),
+        ProtoValue::I64(n) => expected.as_i64() == Some(*n),
+        ProtoValue::U32(n) => expected.as_u64() == Some(*n as u64),
+        ProtoValue::U64(n) => expected.as_u64() == Some(*n),
+        ProtoValue::F32(f) => expected.as_f64().map(|v| v as f32) == Some(*f),
+        ProtoValue::F64(f) => expected.as_f64() == Some(*f),
+        ProtoValue::String(s) => expected.as_str() == Some(s.as_str()),
+        ProtoValue::EnumNumber(n) => match expected {
+            Value::String(s) => match resolve_enum(kind, s) {
+                Some(number) => number == *n,
+                None => {
+                    errors.push(format!(
+                        "{} expected {} which is not a value of the enum",
+                        path, expected
+                    ));
+                    return;
+                }
+            },
+            _ => expected.as_i64() == Some(*n as i64),
+        },
+        ProtoValue::Message(message) => {
+            match expected.as_object() {
+                Some(object) => compare_message(path, message, object, errors),
+                None => errors.push(format!("{} expected {} but got a message", path, expected)),
+            }
+            return;
+        }
+        ProtoValue::List(items) => {
+            let expected_items: &Vec<Value> = match expected.as_array() {
+                Some(expected_items) => expected_items,
+                None => {
+                    errors.push(format!("{} expected {} but got a list", path, expected));
+                    return;
+                }
+            };
+            if expected_items.len() != items.len() {
+                errors.push(format!(
+                    "{} length {} != {}",
+                    path,
+                    items.len(),
+                    expected_items.len()
+                ));
+                return;
+            }
+            for (index, (expected_item, item)) in
+                expected_items.iter().zip(items.iter()).enumerate()
+            {
+                compare_value(
+                    &format!("{}[{}]", path, index),
+                    kind,
+                    expected_item,
+                    item,
+                    errors,
+                );
+            }
+            return;
+        }
+        _ => false,
+    };
+    if !equal {
+        errors.push(format!(
+            "{} expected {} but got {:?}",
+            path, expected, actual
+        ));
+    }
+}
+
+/**
+ * 将表数据中的枚举值解析为枚举编号，支持数字和枚举名称
+ * @param kind 字段类型
+ * @param value 枚举值
+ * @return 枚举编号，无法解析时返回None
+ */
+fn resolve_enum(kind: &Kind, value: &str) -> Option<i32> {
+    if let Ok(number) = value.parse::<i32>() {
+        return Some(number);
+    }
+    match kind {
+        Kind::Enum(descriptor) => descriptor
+            .get_value_by_name(value)
+            .map(|enum_value| enum_value.number()),
+        _ => None,
+    }
+}
@@ -1,24 +1,53 @@
 use crate::{
     common::{
+        build_cache, constant,
+        convert::{convert, verify_protobuff},
         generate::{
             generate_cangjie, generate_cpp, generate_csharp, generate_golang, generate_java,
             generate_javascript, generate_lua, generate_protobuff, generate_python, generate_rust,
             generate_typescript,
         },
-        res,
+        output, overlay, res,
         setting::{self, BuildSetting},
         utils,
     },
     gui::datas::{
-        edevelop_type::EDevelopType, etarget_type::ETargetType, gables, tree_data::TreeData,
+        edevelop_type::EDevelopType,
+        etarget_type::ETargetType,
+        gables,
+        log::{LogRecord, LogTrace},
+        table_data::TableData,
+        tree_data::TreeData,
         tree_item::TreeItem,
     },
 };
-use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex};
+use rayon::prelude::*;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::PathBuf,
+    slice,
+    sync::{Arc, RwLock},
+};
 use std::{process::Command, sync::LazyLock};
+use tera::{Context, Tera};
+
+/**
+ * 已加载的模板，key为构建设置标识
+ */
+static TEMPLATES: LazyLock<RwLock<HashMap<String, Arc<LoadedTemplates>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
 
-static TEMPLATES: LazyLock<Mutex<HashMap<String, String>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
+/**
+ * 构建设置使用的模板，编译一次后所有表共用（可以在多个线程中同时渲染）
+ */
+pub struct LoadedTemplates {
+    /// 编译好的模板，类模板和枚举模板以`templates/平台/class.tpl`、`templates/平台/enums.tpl`注册，Protobuff为`templates/proto/...`
+    pub tera: Tera,
+    /// 类模板和枚举模板的内容
+    pub contents: Vec<String>,
+}
 
 #[derive(serde::Serialize)]
 pub struct GenerateMainFieldItem {
@@ -48,39 +77,78 @@ pub struct GenerateFieldInfo {
 }
 
 /**
- * 批量生成代码（所有平台 & 所有表单）
+ * 批量生成代码（所有平台 & 所有表单），各平台并行生成
+ * @param force 是否忽略构建缓存，重新生成所有表
 */
-pub fn from_all() {
+pub fn from_all(force: bool) {
     let settings = setting::APP_SETTINGS.read().unwrap();
-    for setting in settings.build_settings.iter() {
-        from_target(setting);
-    }
+    clear_templates();
+    overlay::clear();
+    build_cache::load();
+    let logs: Vec<Vec<LogRecord>> = settings
+        .build_settings
+        .par_iter()
+        .map(|setting| LogTrace::capture(|| generate_target(setting, force)).1)
+        .collect();
+    // 按构建设置的顺序输出日志
+    logs.into_iter().for_each(LogTrace::emit);
+    build_cache::save();
 }
 
 /**
- * 批量生成代码（指定平台 & 所有表单）
+ * 批量生成代码（指定平台 & 所有表单），内容没有变化的表会跳过，都没有变化时不执行校验和后处理命令
  * @param setting 指定的平台
+ * @param force 是否忽略构建缓存，重新生成所有表
+*/
+pub fn from_target(build_setting: &BuildSetting, force: bool) {
+    overlay::clear();
+    build_cache::load();
+    generate_target(build_setting, force);
+    build_cache::save();
+}
+
+/**
+ * 生成指定平台的所有表，调用前后需要读取和保存构建缓存
+ * @param build_setting 指定的平台
+ * @param force 是否忽略构建缓存，重新生成所有表
 */
-pub fn from_target(build_setting: &BuildSetting) {
+fn generate_target(build_setting: &BuildSetting, force: bool) {
     if !build_setting.generate_script {
         return;
     }
     let items = gables::TREE_ITEMS.read().unwrap();
-    let mut datas: HashMap<String, &TreeData> = HashMap::new();
-    for item in items.iter() {
-        let item_datas: HashMap<String, &TreeData> = item.get_datas();
-        if item_datas.len() > 0 {
-            datas.extend(item_datas);
-        }
-    }
+    let datas: BTreeMap<String, Cow<TreeData>> =
+        match TreeItem::collect_tables(&items, &items, build_setting) {
+            Some(datas) => datas,
+            None => return,
+        };
     if datas.len() <= 0 {
         log::error!("No configurations found to export");
         return;
     }
-    clear_templates();
     preload_templates(build_setting);
-    for (_, data) in datas.iter() {
-        execute(build_setting, *data);
+    let previous: BTreeSet<String> =
+        build_cache::get_outputs(build_cache::SCOPE_SCRIPT, build_setting);
+    let tables_changed: bool =
+        build_cache::tables_changed(build_cache::SCOPE_SCRIPT, build_setting, &datas);
+    let rebuilt: usize = execute_tables(build_setting, &datas, force);
+    if rebuilt > 0 || tables_changed {
+        generate_bundle(build_setting, &datas);
+        if build_setting.verify_protobuf {
+            verify_protobuff::from_tables(build_setting, &datas);
+        }
+    }
+    build_cache::retain_tables(build_cache::SCOPE_SCRIPT, build_setting, &datas);
+    output::clean_stale(build_cache::SCOPE_SCRIPT, build_setting, &previous);
+    log::info!(
+        "Generate [{}] finished: {} rebuilt, {} unchanged",
+        build_setting.display_name,
+        rebuilt,
+        datas.len() - rebuilt
+    );
+    output::report(build_setting, "Generate");
+    if rebuilt == 0 && !tables_changed {
+        return;
     }
 
     if !build_setting.postprocessing.is_empty() {
@@ -90,75 +158,234 @@ pub fn from_target(build_setting: &BuildSetting) {
 }
 
 /**
- * 批量转换（所有平台 & 指定表单）
+ * 批量转换（所有平台 & 指定表单），指定的表总是重新生成，各平台并行生成
  * @param item 指定的表单
 */
 pub fn from_items(item: &TreeItem) {
-    let datas: HashMap<String, &TreeData> = item.get_datas();
-    if datas.len() <= 0 {
+    if item.get_datas().len() <= 0 {
         log::error!("Obtaining data is empty: {}", item.display_name);
         return;
     }
     clear_templates();
+    overlay::clear();
+    build_cache::load();
+    let items = gables::TREE_ITEMS.read().unwrap();
     let settings = setting::APP_SETTINGS.read().unwrap();
-    for build_setting in settings.build_settings.iter() {
-        if !build_setting.generate_script {
-            continue;
-        }
-        preload_templates(build_setting);
-        for (_, data) in datas.iter() {
-            execute(build_setting, *data);
-        }
-        if !build_setting.postprocessing.is_empty() {
-            let target_path: PathBuf = utils::get_absolute_path(&setting::get_workspace());
-            system_command(&build_setting.postprocessing, &target_path);
+    let logs: Vec<Vec<LogRecord>> = settings
+        .build_settings
+        .par_iter()
+        .map(|build_setting| {
+            LogTrace::capture(|| {
+                if !build_setting.generate_script {
+                    return;
+                }
+                preload_templates(build_setting);
+                let previous: BTreeSet<String> =
+                    build_cache::get_outputs(build_cache::SCOPE_SCRIPT, build_setting);
+                let datas: BTreeMap<String, Cow<TreeData>> =
+                    match TreeItem::collect_tables(&items, slice::from_ref(item), build_setting) {
+                        Some(datas) => datas,
+                        None => return,
+                    };
+                // 数据库文件需要包含所有表
+                let all_datas: Option<BTreeMap<String, Cow<TreeData>>> =
+                    if build_setting.proto_bundle {
+                        match TreeItem::collect_tables(&items, &items, build_setting) {
+                            Some(all_datas) => Some(all_datas),
+                            None => return,
+                        }
+                    } else {
+                        None
+                    };
+                let tables_changed: bool = all_datas.as_ref().is_some_and(|all_datas| {
+                    build_cache::tables_changed(build_cache::SCOPE_SCRIPT, build_setting, all_datas)
+                });
+                let rebuilt: usize = execute_tables(build_setting, &datas, true);
+                if let Some(all_datas) = all_datas.as_ref()
+                    && (rebuilt > 0 || tables_changed)
+                {
+                    generate_bundle(build_setting, all_datas);
+                }
+                if build_setting.verify_protobuf {
+                    verify_protobuff::from_tables(build_setting, &datas);
+                }
+                output::clean_stale(build_cache::SCOPE_SCRIPT, build_setting, &previous);
+                output::report(build_setting, "Generate");
+                if !build_setting.postprocessing.is_empty() {
+                    let target_path: PathBuf = utils::get_absolute_path(&setting::get_workspace());
+                    system_command(&build_setting.postprocessing, &target_path);
+                }
+            })
+            .1
+        })
+        .collect();
+    logs.into_iter().for_each(LogTrace::emit);
+    build_cache::save();
+}
+
+/**
+ * 向模板上下文写入数据格式相关的设置，生成的加载代码可以据此选择解析方式
+ * TARGET_TYPE 导出类型；JSON_LAYOUT json数据布局（Array、Keyed、Columns）；JSON_MINIFY json是否压缩
+ * @param context 模板上下文
+ * @param build_setting 构建设置
+ */
+pub fn insert_settings(context: &mut Context, build_setting: &BuildSetting) {
+    context.insert("TARGET_TYPE", build_setting.target_type.as_str());
+    context.insert("JSON_LAYOUT", build_setting.json_layout.as_str());
+    context.insert("JSON_MINIFY", &build_setting.json_minify);
+}
+
+/**
+ * 向模板上下文写入表所在目录相关的变量，按目录结构输出时生成代码的命名空间和导入路径包含目录
+ * MIRROR_FOLDERS 是否按目录结构输出；NAMESPACE 命名空间（目录名列表）；IMPORT_PREFIX 从生成的文件回到脚本根目录的相对路径；
+ * IMPORT_FILES 引用的类型相对于脚本根目录的路径（不含扩展名）；IMPORT_NAMESPACES 引用的类型所在的命名空间；
+ * USINGS 需要引用的其他命名空间
+ * 不按目录结构输出时所有类型都在根目录
+ * @param context 模板上下文
+ * @param build_setting 构建设置
+ * @param table_data 表数据
+ * @param imports 引用的类型名称
+ */
+pub fn insert_folder(
+    context: &mut Context,
+    build_setting: &BuildSetting,
+    table_data: &TableData,
+    imports: &[String],
+) {
+    let mirror: bool = output::is_mirror_folders(build_setting);
+    let folder: &str = if mirror { &table_data.folder } else { "" };
+    let mut import_files: BTreeMap<&str, String> = BTreeMap::new();
+    let mut import_namespaces: BTreeMap<&str, String> = BTreeMap::new();
+    let mut usings: BTreeSet<String> = BTreeSet::new();
+    for import in imports.iter() {
+        let import_folder: String = if mirror {
+            gables::get_table_folder(import, folder).unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let namespace: String = to_namespace(&import_folder).join(".");
+        if import_folder.is_empty() {
+            import_files.insert(import, import.clone());
+        } else {
+            import_files.insert(import, format!("{}/{}", import_folder, import));
+            if import_folder != folder {
+                usings.insert(namespace.clone());
+            }
         }
+        import_namespaces.insert(import, namespace);
     }
+    let import_prefix: String = if folder.is_empty() {
+        "./".to_string()
+    } else {
+        "../".repeat(folder.split('/').count())
+    };
+    context.insert("MIRROR_FOLDERS", &mirror);
+    context.insert("NAMESPACE", &to_namespace(folder));
+    context.insert("IMPORT_PREFIX", &import_prefix);
+    context.insert("IMPORT_FILES", &import_files);
+    context.insert("IMPORT_NAMESPACES", &import_namespaces);
+    context.insert("USINGS", &usings);
 }
 
-pub fn get_template(key: &str) -> Option<String> {
-    let templates = TEMPLATES.lock().unwrap();
-    templates.get(key).cloned()
+/**
+ * 将目录转换为命名空间，目录名中不能用于标识符的字符替换为`_`，数字开头时加上`_`
+ * @param folder 目录，以`/`分隔
+ * @return 命名空间的各级名称
+ */
+fn to_namespace(folder: &str) -> Vec<String> {
+    folder
+        .split('/')
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            let mut identifier: String = name
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                .collect();
+            if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+                identifier.insert(0, '_');
+            }
+            identifier
+        })
+        .collect()
+}
+
+/**
+ * 获取构建设置预加载的模板
+ * @param build_setting 构建设置
+ * @return 模板，没有预加载时返回None
+ */
+pub fn get_templates(build_setting: &BuildSetting) -> Option<Arc<LoadedTemplates>> {
+    let templates = TEMPLATES.read().unwrap();
+    let loaded: Option<Arc<LoadedTemplates>> = templates.get(&build_setting.id).cloned();
+    if loaded.is_none() {
+        log::error!(
+            "Templates of [{}] are not loaded",
+            build_setting.display_name
+        );
+    }
+    loaded
 }
 
 pub fn clear_templates() {
-    let mut templates = TEMPLATES.lock().unwrap();
+    let mut templates = TEMPLATES.write().unwrap();
     templates.clear();
 }
 
+/**
+ * 预加载构建设置使用的模板并编译，重复调用时替换之前加载的模板
+ * @param build_setting 构建设置
+ */
 pub fn preload_templates(build_setting: &BuildSetting) {
-    let mut templates = TEMPLATES.lock().unwrap();
-    if build_setting.target_type == ETargetType::Protobuff {
-        let template_path = if build_setting.is_proto_2 {
-            "templates/proto2/class.tpl".to_string()
+    let [class_key, enum_key] = get_template_keys(build_setting);
+    let (class_path, enum_path): (String, String) =
+        if build_setting.target_type == ETargetType::Protobuff {
+            if build_setting.is_proto_2 {
+                (
+                    "templates/proto2/class.tpl".to_string(),
+                    "templates/proto2/enums.tpl".to_string(),
+                )
+            } else {
+                (
+                    "templates/proto3/class.tpl".to_string(),
+                    "templates/proto3/enums.tpl".to_string(),
+                )
+            }
         } else {
-            "templates/proto3/class.tpl".to_string()
+            (class_key.clone(), enum_key.clone())
         };
-        templates.insert(
+    let contents: Vec<String> = vec![
+        load_template(build_setting, &class_path, "class.tpl"),
+        load_template(build_setting, &enum_path, "enums.tpl"),
+    ];
+    let mut tera: Tera = Tera::default();
+    for (key, content) in [&class_key, &enum_key].iter().zip(contents.iter()) {
+        if let Err(e) = tera.add_raw_template(key, content) {
+            log::error!("Template error: {} - {}", key, e);
+        }
+    }
+    let mut templates = TEMPLATES.write().unwrap();
+    templates.insert(
+        build_setting.id.clone(),
+        Arc::new(LoadedTemplates { tera, contents }),
+    );
+}
+
+/**
+ * 构建设置使用的类模板和枚举模板的key
+ * @param build_setting 构建设置
+ */
+fn get_template_keys(build_setting: &BuildSetting) -> [String; 2] {
+    if build_setting.target_type == ETargetType::Protobuff {
+        [
             "templates/proto/class.tpl".to_string(),
-            load_template(build_setting, &template_path, "class.tpl"),
-        );
-        let enum_path = if build_setting.is_proto_2 {
-            "templates/proto2/enums.tpl".to_string()
-        } else {
-            "templates/proto3/enums.tpl".to_string()
-        };
-        templates.insert(
             "templates/proto/enums.tpl".to_string(),
-            load_template(build_setting, &enum_path, "enums.tpl"),
-        );
+        ]
     } else {
         let path_keyword = build_setting.dev.path_keyword();
-        let template_path: String = format!("templates/{}/class.tpl", path_keyword);
-        templates.insert(
-            template_path.clone(),
-            load_template(build_setting, &template_path, "class.tpl"),
-        );
-        let enum_path: String = format!("templates/{}/enums.tpl", path_keyword);
-        templates.insert(
-            enum_path.clone(),
-            load_template(build_setting, &enum_path, "enums.tpl"),
-        );
+        [
+            format!("templates/{}/class.tpl", path_keyword),
+            format!("templates/{}/enums.tpl", path_keyword),
+        ]
     }
 }
 
@@ -183,28 +410,107 @@ fn load_template(build_setting: &BuildSetting, builtin: &str, file_name: &str) -
     tempalte_content
 }
 
+/**
+ * 生成汇总所有表的数据库proto文件，并记录输出的文件
+ * @param build_setting 构建设置
+ * @param all_datas 所有的表
+ */
+pub fn generate_bundle(build_setting: &BuildSetting, all_datas: &BTreeMap<String, Cow<TreeData>>) {
+    let (_, outputs) = output::capture(|| generate_protobuff::to_bundle(build_setting, all_datas));
+    build_cache::set_outputs(
+        build_cache::SCOPE_SCRIPT,
+        build_setting,
+        constant::PROTO_BUNDLE_NAME,
+        outputs,
+    );
+}
+
+/**
+ * 并行生成多个表，日志按表名顺序输出，与逐个生成时相同
+ * @param build_setting 构建设置
+ * @param datas 表名和表数据的映射
+ * @param force 是否忽略构建缓存
+ * @return 生成的表数量
+ */
+pub fn execute_tables(
+    build_setting: &BuildSetting,
+    datas: &BTreeMap<String, Cow<TreeData>>,
+    force: bool,
+) -> usize {
+    let datas: Vec<&TreeData> = datas.values().map(|data| data.as_ref()).collect();
+    let results: Vec<(bool, Vec<LogRecord>)> = datas
+        .par_iter()
+        .map(|data| LogTrace::capture(|| execute(build_setting, data, force)))
+        .collect();
+    let mut rebuilt: usize = 0;
+    for (is_rebuilt, records) in results {
+        LogTrace::emit(records);
+        if is_rebuilt {
+            rebuilt += 1;
+        }
+    }
+    rebuilt
+}
+
 /**
  * 执行生成代码
+ * 内容哈希与构建缓存中的记录相同时跳过，调用前需要预加载模板，调用前后需要读取和保存构建缓存；可以在多个线程中同时调用
  * @param build_setting 构建设置
  * @param data 树数据
+ * @param force 是否忽略构建缓存
+ * @return 是否生成了该表
 */
-pub fn execute(build_setting: &BuildSetting, data: &TreeData) {
-    if build_setting.target_type == ETargetType::Protobuff {
-        generate_protobuff::to(build_setting, data);
-    } else {
-        match build_setting.dev {
-            EDevelopType::Cpp => generate_cpp::to(build_setting, data),
-            EDevelopType::Csharp => generate_csharp::to(build_setting, data),
-            EDevelopType::Cangjie => generate_cangjie::to(build_setting, data),
-            EDevelopType::Golang => generate_golang::to(build_setting, data),
-            EDevelopType::Java => generate_java::to(build_setting, data),
-            EDevelopType::JavaScript => generate_javascript::to(build_setting, data),
-            EDevelopType::Lua => generate_lua::to(build_setting, data),
-            EDevelopType::Python => generate_python::to(build_setting, data),
-            EDevelopType::TypeScript => generate_typescript::to(build_setting, data),
-            EDevelopType::Rust => generate_rust::to(build_setting, data),
+pub fn execute(build_setting: &BuildSetting, data: &TreeData, force: bool) -> bool {
+    let source: &TreeData = data;
+    let data: Cow<TreeData> = match convert::transform(build_setting, source) {
+        Some(data) => data,
+        None => return false,
+    };
+    let table_key: String = output::get_table_key(build_setting, &data.folder, &data.file_name);
+    let table_data: TableData = match TableData::new(&data, &build_setting.keyword) {
+        Some(table_data) => table_data,
+        None => {
+            build_cache::forget(build_cache::SCOPE_SCRIPT, build_setting, &table_key);
+            return false;
         }
+    };
+    let table_data: &TableData = &table_data;
+    let templates: Vec<String> = get_templates(build_setting)
+        .map(|templates| templates.contents.clone())
+        .unwrap_or_default();
+    let hash: String = build_cache::table_hash(build_setting, &data, table_data, &templates);
+    if !force
+        && build_cache::is_unchanged(build_cache::SCOPE_SCRIPT, build_setting, &table_key, &hash)
+    {
+        log::debug!("Skipped unchanged table: {}", table_key);
+        return false;
     }
+    let (_, outputs) = output::capture(|| {
+        if build_setting.target_type == ETargetType::Protobuff {
+            generate_protobuff::to(build_setting, source, table_data);
+        } else {
+            match build_setting.dev {
+                EDevelopType::Cpp => generate_cpp::to(build_setting, table_data),
+                EDevelopType::Csharp => generate_csharp::to(build_setting, table_data),
+                EDevelopType::Cangjie => generate_cangjie::to(build_setting, table_data),
+                EDevelopType::Golang => generate_golang::to(build_setting, table_data),
+                EDevelopType::Java => generate_java::to(build_setting, table_data),
+                EDevelopType::JavaScript => generate_javascript::to(build_setting, table_data),
+                EDevelopType::Lua => generate_lua::to(build_setting, table_data),
+                EDevelopType::Python => generate_python::to(build_setting, table_data),
+                EDevelopType::TypeScript => generate_typescript::to(build_setting, table_data),
+                EDevelopType::Rust => generate_rust::to(build_setting, table_data),
+            }
+        }
+    });
+    build_cache::update(
+        build_cache::SCOPE_SCRIPT,
+        build_setting,
+        &table_key,
+        hash,
+        outputs,
+    );
+    true
 }
 
 /**
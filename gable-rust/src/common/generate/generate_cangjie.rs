@@ -1,50 +1,44 @@
 use crate::{
     common::{
-        generate::generate::{self, GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem},
+        generate::generate::{
+            self, GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem, LoadedTemplates,
+        },
+        output,
         setting::BuildSetting,
-        utils,
     },
     gui::datas::{
-        edata_type::EDataType,
-        esheet_type::ESheetType,
-        tree_data::{FieldInfo, TreeData},
+        edata_type::EDataType, esheet_type::ESheetType, table_data::TableData, tree_data::FieldInfo,
     },
 };
-use std::{fs, io::Error, path::PathBuf};
+use std::{io::Error, path::PathBuf, sync::Arc};
 use tera::{Context, Tera};
 
 /**
  * 生成仓颉语言脚本
  * @param build_setting 构建设置
- * @param tree_data 树数据
+ * @param table_data 表数据
 */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    let field_info: FieldInfo = if let Some(info) = tree_data.to_fields(&build_setting.keyword) {
-        info
-    } else {
-        return;
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    let field_info: &FieldInfo = &table_data.field_info;
+    let cangjie_fields: GenerateFieldInfo = transition_fields(field_info);
+    let templates: Arc<LoadedTemplates> = match generate::get_templates(build_setting) {
+        Some(templates) => templates,
+        None => return,
     };
-    let cangjie_fields: GenerateFieldInfo = transition_fields(&field_info);
-    let mut tera: Tera = Tera::default();
+    let tera: &Tera = &templates.tera;
     let class_key = "templates/cangjie/class.tpl";
-    if let Some(content) = generate::get_template(class_key) {
-        tera.add_raw_template(class_key, &content)
-            .expect("Cangjie Failed to add class template");
-    }
     let enum_key = "templates/cangjie/enums.tpl";
-    if let Some(content) = generate::get_template(enum_key) {
-        tera.add_raw_template(enum_key, &content)
-            .expect("Cangjie Failed to add enum template");
-    }
     let mut context: Context = Context::new();
-    context.insert("CLASS_NAME", &tree_data.file_name);
+    context.insert("CLASS_NAME", &table_data.file_name);
+    generate::insert_settings(&mut context, build_setting);
     context.insert("info", &cangjie_fields);
 
     // 收集导入的模块
     let imports: Vec<String> = collect_imports(&cangjie_fields);
     context.insert("imports", &imports);
+    generate::insert_folder(&mut context, build_setting, table_data, &imports);
 
-    let rendered_result: Result<String, tera::Error> = match tree_data.gable_type {
+    let rendered_result: Result<String, tera::Error> = match table_data.gable_type {
         ESheetType::Normal | ESheetType::Localize | ESheetType::KV => {
             tera.render(class_key, &context)
         }
@@ -55,10 +49,14 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
         return;
     }
     let rendered: String = rendered_result.unwrap_or(String::new());
-    let target_path: PathBuf = utils::get_absolute_path(&build_setting.script_path)
-        .join(format!("{}.cj", tree_data.file_name));
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.script_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.cj", table_data.file_name));
 
-    let result: Result<(), Error> = fs::write(&target_path, rendered);
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, rendered);
     if result.is_err() {
         log::error!(
             "Export [{}] failed: {}",
@@ -147,9 +145,9 @@ fn transition_fields(info: &FieldInfo) -> GenerateFieldInfo {
 }
 
 /**
- * 收集要导入的模块
+ * 收集要导入的类型
  * @param fields 字段信息
- * @return 导入的模块列表
+ * @return 导入的类型名称列表，包路径由模板按所在目录拼接
 */
 fn collect_imports(info: &GenerateFieldInfo) -> Vec<String> {
     let mut imports: Vec<String> = Vec::new();
@@ -168,7 +166,7 @@ fn collect_imports(info: &GenerateFieldInfo) -> Vec<String> {
                 && field.field_type != "Vector3"
                 && field.field_type != "Vector4"
             {
-                imports.push(format!("gable.{}", field.field_type));
+                imports.push(field.field_type.clone());
             }
         } else if field.field_type.starts_with("Array<") {
             // 处理数组类型中的自定义类型
@@ -179,7 +177,7 @@ fn collect_imports(info: &GenerateFieldInfo) -> Vec<String> {
                 && element_type != "Float32"
                 && !imports.contains(&element_type.to_string())
             {
-                imports.push(format!("gable.{}", element_type));
+                imports.push(element_type.to_string());
             }
         }
     }
@@ -1,45 +1,41 @@
 use crate::{
     common::{
-        generate::generate::{self, GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem},
+        generate::generate::{
+            self, GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem, LoadedTemplates,
+        },
+        output,
         setting::BuildSetting,
-        utils,
     },
     gui::datas::{
-        edata_type::EDataType,
-        esheet_type::ESheetType,
-        tree_data::{FieldInfo, TreeData},
+        edata_type::EDataType, esheet_type::ESheetType, table_data::TableData, tree_data::FieldInfo,
     },
 };
-use std::{fs, io::Error, path::PathBuf};
+use std::{io::Error, path::PathBuf, sync::Arc};
 use tera::{Context, Tera};
 
 /**
  * 生成C#脚本
  * @param build_setting 构建设置
- * @param tree_data 树结构数据
+ * @param table_data 表数据
 */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    let field_info: FieldInfo = if let Some(info) = tree_data.to_fields(&build_setting.keyword) {
-        info
-    } else {
-        return;
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    let field_info: &FieldInfo = &table_data.field_info;
+    let generate_info: GenerateFieldInfo = transition_fields(field_info);
+    let templates: Arc<LoadedTemplates> = match generate::get_templates(build_setting) {
+        Some(templates) => templates,
+        None => return,
     };
-    let generate_info: GenerateFieldInfo = transition_fields(&field_info);
-    let mut tera: Tera = Tera::default();
+    let tera: &Tera = &templates.tera;
     let class_key = "templates/csharp/class.tpl";
-    if let Some(content) = generate::get_template(class_key) {
-        tera.add_raw_template(class_key, &content)
-            .expect("Csharp Failed to add class template");
-    }
     let enum_key = "templates/csharp/enums.tpl";
-    if let Some(content) = generate::get_template(enum_key) {
-        tera.add_raw_template(enum_key, &content)
-            .expect("Csharp Failed to add enum template");
-    }
     let mut context: Context = Context::new();
-    context.insert("CLASS_NAME", &tree_data.file_name);
+    context.insert("CLASS_NAME", &table_data.file_name);
+    generate::insert_settings(&mut context, build_setting);
     context.insert("info", &generate_info);
-    let rendered_result: Result<String, tera::Error> = match tree_data.gable_type {
+    // 引用的枚举类型，按目录结构输出时需要引用所在的命名空间
+    let imports: Vec<String> = collect_imports(field_info);
+    generate::insert_folder(&mut context, build_setting, table_data, &imports);
+    let rendered_result: Result<String, tera::Error> = match table_data.gable_type {
         ESheetType::Normal | ESheetType::Localize | ESheetType::KV => {
             tera.render(class_key, &context)
         }
@@ -50,10 +46,14 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
         return;
     }
     let rendered: String = rendered_result.unwrap_or(String::new());
-    let target_path: PathBuf = utils::get_absolute_path(&build_setting.script_path)
-        .join(format!("{}.cs", tree_data.file_name));
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.script_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.cs", table_data.file_name));
 
-    let result: Result<(), Error> = fs::write(&target_path, rendered);
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, rendered);
     if result.is_err() {
         log::error!(
             "Export [{}] failed: {}",
@@ -69,6 +69,28 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
     }
 }
 
+/**
+ * 收集引用的枚举类型
+ * @param info 字段信息
+ * @return 枚举类型列表
+*/
+fn collect_imports(info: &FieldInfo) -> Vec<String> {
+    let mut imports: Vec<String> = Vec::new();
+    for field in info.fields.iter() {
+        if field.field_type != EDataType::Enum || field.field_link.is_empty() {
+            continue;
+        }
+        let enum_name: &str = match field.field_link.find("@") {
+            Some(pos) => &field.field_link[pos + 1..],
+            None => &field.field_link,
+        };
+        if !imports.iter().any(|import| import == enum_name) {
+            imports.push(enum_name.to_string());
+        }
+    }
+    imports
+}
+
 /**
  * 通用字段转换成C#字段
  * @param fields 字段列表
@@ -1,50 +1,44 @@
 use crate::{
     common::{
-        generate::generate::{self, GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem},
+        generate::generate::{
+            self, GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem, LoadedTemplates,
+        },
+        output,
         setting::BuildSetting,
-        utils,
     },
     gui::datas::{
-        edata_type::EDataType,
-        esheet_type::ESheetType,
-        tree_data::{FieldInfo, TreeData},
+        edata_type::EDataType, esheet_type::ESheetType, table_data::TableData, tree_data::FieldInfo,
     },
 };
-use std::{fs, io::Error, path::PathBuf};
+use std::{io::Error, path::PathBuf, sync::Arc};
 use tera::{Context, Tera};
 
 /**
  * golang语言生成
  * @param build_setting 构建设置
- * @param tree_data 树数据
+ * @param table_data 表数据
 */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    let field_info: FieldInfo = if let Some(info) = tree_data.to_fields(&build_setting.keyword) {
-        info
-    } else {
-        return;
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    let field_info: &FieldInfo = &table_data.field_info;
+    let go_fields: GenerateFieldInfo = transition_fields(field_info);
+    let templates: Arc<LoadedTemplates> = match generate::get_templates(build_setting) {
+        Some(templates) => templates,
+        None => return,
     };
-    let go_fields: GenerateFieldInfo = transition_fields(&field_info);
-    let mut tera: Tera = Tera::default();
+    let tera: &Tera = &templates.tera;
     let class_key = "templates/golang/class.tpl";
-    if let Some(content) = generate::get_template(class_key) {
-        tera.add_raw_template(class_key, &content)
-            .expect("Golang Failed to add class template");
-    }
     let enum_key = "templates/golang/enums.tpl";
-    if let Some(content) = generate::get_template(enum_key) {
-        tera.add_raw_template(enum_key, &content)
-            .expect("Golang Failed to add enum template");
-    }
     let mut context: Context = Context::new();
-    context.insert("CLASS_NAME", &tree_data.file_name);
+    context.insert("CLASS_NAME", &table_data.file_name);
+    generate::insert_settings(&mut context, build_setting);
     context.insert("info", &go_fields);
 
     // 收集导入的包
     let imports: Vec<String> = collect_imports(&go_fields);
     context.insert("imports", &imports);
+    generate::insert_folder(&mut context, build_setting, table_data, &imports);
 
-    let rendered_result: Result<String, tera::Error> = match tree_data.gable_type {
+    let rendered_result: Result<String, tera::Error> = match table_data.gable_type {
         ESheetType::Normal | ESheetType::Localize | ESheetType::KV => {
             tera.render(class_key, &context)
         }
@@ -55,10 +49,14 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
         return;
     }
     let rendered: String = rendered_result.unwrap_or(String::new());
-    let target_path: PathBuf = utils::get_absolute_path(&build_setting.script_path)
-        .join(format!("{}.go", tree_data.file_name));
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.script_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.go", table_data.file_name));
 
-    let result: Result<(), Error> = fs::write(&target_path, rendered);
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, rendered);
     if result.is_err() {
         log::error!(
             "Export [{}] failed: {}",
@@ -133,12 +131,23 @@ fn transition_fields(info: &FieldInfo) -> GenerateFieldInfo {
                 &enum_name.clone()
             }
         };
+        // 扩展信息记录引用的表名，按目录结构输出时模板据此导入包并加上包名
+        let link_table: String = if field.field_type == EDataType::Enum {
+            field
+                .field_link
+                .rsplit('@')
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        } else {
+            String::new()
+        };
         let go_field: GenerateFieldItem = GenerateFieldItem {
             field_name: field_name,
             field_type: go_type.to_string(),
             field_desc: field.field_desc.clone(),
             field_index: field.field_index,
-            field_extend: String::new(),
+            field_extend: link_table,
             data_type: String::new(),
         };
         fields.push(go_field);
@@ -151,51 +160,16 @@ fn transition_fields(info: &FieldInfo) -> GenerateFieldInfo {
 }
 
 /**
- * 收集导入的模块
+ * 收集引用的类型
  * @param fields 字段列表
- * @return 导入的模块列表
+ * @return 引用的表名列表，导入路径由模板按所在目录拼接
 */
 fn collect_imports(info: &GenerateFieldInfo) -> Vec<String> {
     let mut imports: Vec<String> = Vec::new();
-
     for field in info.fields.iter() {
-        // 检查是否有需要导入的自定义类型
-        if field.field_type != "int"
-            && field.field_type != "int64"
-            && field.field_type != "string"
-            && field.field_type != "bool"
-            && field.field_type != "float64"
-            && !field.field_type.starts_with("[]")
-        {
-            // 对于自定义类型，可能需要添加导入路径
-            if !imports.contains(&field.field_type)
-                && field.field_type != "Vector2"
-                && field.field_type != "Vector3"
-                && field.field_type != "Vector4"
-            {
-                // 这里可以添加实际的导入路径逻辑
-                // 暂时使用简单的处理方式
-                imports.push(format!(
-                    "example.com/project/{}",
-                    field.field_type.to_lowercase()
-                ));
-            }
-        } else if field.field_type.starts_with("[]") {
-            // 处理数组类型中的自定义类型
-            let element_type = &field.field_type[2..]; // 移除 "[]"
-            if element_type != "int"
-                && element_type != "string"
-                && element_type != "bool"
-                && element_type != "float64"
-                && !imports.contains(&element_type.to_string())
-            {
-                imports.push(format!(
-                    "example.com/project/{}",
-                    element_type.to_lowercase()
-                ));
-            }
+        if !field.field_extend.is_empty() && !imports.contains(&field.field_extend) {
+            imports.push(field.field_extend.clone());
         }
     }
-
     imports
 }
@@ -1,15 +1,15 @@
-use std::{fs, io::Error, path::PathBuf};
+use std::{io::Error, path::PathBuf, sync::Arc};
 
 use crate::{
     common::{
-        generate::generate::{self, GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem},
+        generate::generate::{
+            self, GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem, LoadedTemplates,
+        },
+        output,
         setting::BuildSetting,
-        utils,
     },
     gui::datas::{
-        edata_type::EDataType,
-        esheet_type::ESheetType,
-        tree_data::{FieldInfo, TreeData},
+        edata_type::EDataType, esheet_type::ESheetType, table_data::TableData, tree_data::FieldInfo,
     },
 };
 use tera::{Context, Tera};
@@ -17,35 +17,29 @@ use tera::{Context, Tera};
 /**
  * 生成Java代码
  * @param build_setting 构建设置
- * @param tree_data 树结构数据
+ * @param table_data 表数据
 */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    let field_info: FieldInfo = if let Some(info) = tree_data.to_fields(&build_setting.keyword) {
-        info
-    } else {
-        return;
+pub fn to(build_setting: &BuildSetting, table_data: &TableData) {
+    let field_info: &FieldInfo = &table_data.field_info;
+    let java_fields: GenerateFieldInfo = transition_fields(field_info);
+    let templates: Arc<LoadedTemplates> = match generate::get_templates(build_setting) {
+        Some(templates) => templates,
+        None => return,
     };
-    let java_fields: GenerateFieldInfo = transition_fields(&field_info);
-    let mut tera: Tera = Tera::default();
+    let tera: &Tera = &templates.tera;
     let class_key = "templates/java/class.tpl";
-    if let Some(content) = generate::get_template(class_key) {
-        tera.add_raw_template(class_key, &content)
-            .expect("Java Failed to add class template");
-    }
     let enum_key = "templates/java/enums.tpl";
-    if let Some(content) = generate::get_template(enum_key) {
-        tera.add_raw_template(enum_key, &content)
-            .expect("Java Failed to add enum template");
-    }
     let mut context: Context = Context::new();
-    context.insert("CLASS_NAME", &tree_data.file_name);
+    context.insert("CLASS_NAME", &table_data.file_name);
+    generate::insert_settings(&mut context, build_setting);
     context.insert("info", &java_fields);
 
     // 收集导入的类
     let imports: Vec<String> = collect_imports(&java_fields);
     context.insert("imports", &imports);
+    generate::insert_folder(&mut context, build_setting, table_data, &imports);
 
-    let rendered_result: Result<String, tera::Error> = match tree_data.gable_type {
+    let rendered_result: Result<String, tera::Error> = match table_data.gable_type {
         ESheetType::Normal | ESheetType::Localize | ESheetType::KV => {
             tera.render(class_key, &context)
         }
@@ -56,10 +50,14 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
         return;
     }
     let rendered: String = rendered_result.unwrap_or(String::new());
-    let target_path: PathBuf = utils::get_absolute_path(&build_setting.script_path)
-        .join(format!("{}.java", tree_data.file_name));
+    let target_path: PathBuf = output::get_table_dir(
+        build_setting,
+        &build_setting.script_path,
+        &table_data.folder,
+    )
+    .join(format!("{}.java", table_data.file_name));
 
-    let result: Result<(), Error> = fs::write(&target_path, rendered);
+    let result: Result<bool, Error> = output::write(build_setting, &target_path, rendered);
     if result.is_err() {
         log::error!(
             "Export [{}] failed: {}",
@@ -1,56 +1,61 @@
 use crate::{
     common::{
+        constant,
         generate::{
-            generate::{self, GenerateFieldInfo, GenerateFieldItem},
+            generate::{self, GenerateFieldInfo, GenerateFieldItem, LoadedTemplates},
             proto_field_info,
+            proto_registry::{self, ProtoRegistryField},
         },
+        output,
         setting::BuildSetting,
         utils,
     },
     gui::datas::{
-        edata_type::EDataType,
-        esheet_type::ESheetType,
-        tree_data::{FieldInfo, TreeData},
+        edata_type::EDataType, esheet_type::ESheetType, etarget_type::ETargetType,
+        table_data::TableData, tree_data::TreeData,
     },
 };
-use std::{io::Error, path::PathBuf};
+use std::{borrow::Cow, collections::BTreeMap, io::Error, path::PathBuf, sync::Arc};
 use tera::{Context, Tera};
 
 /**
  * 生成ProtoBuff文件
  * @param build_setting 构建设置
- * @param tree_data 树数据
+ * @param source 覆盖数据和字段命名之前的树数据，用于同步字段编号表
+ * @param table_data 表数据
 */
-pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
-    let field_info: FieldInfo = if let Some(info) = tree_data.to_fields(&build_setting.keyword) {
-        info
+pub fn to(build_setting: &BuildSetting, source: &TreeData, table_data: &TableData) {
+    let mut allow_alias: bool = false;
+    let (imports, mut proto_fields, common_protos) = if table_data.gable_type == ESheetType::Enum {
+        let (enum_fields, enum_alias) =
+            proto_field_info::transition_enum(table_data, build_setting.is_proto_2);
+        allow_alias = enum_alias;
+        (Vec::new(), enum_fields, Vec::new())
     } else {
-        return;
+        proto_field_info::transition_fields(&table_data.field_info, build_setting.is_proto_2)
     };
-    let (imports, proto_fields, common_protos) =
-        proto_field_info::transition_fields(&field_info, build_setting.is_proto_2);
+    let reserved: Vec<ProtoRegistryField> =
+        proto_registry::apply(build_setting, source, &mut proto_fields);
 
-    let mut tera: Tera = Tera::default();
+    let templates: Arc<LoadedTemplates> = match generate::get_templates(build_setting) {
+        Some(templates) => templates,
+        None => return,
+    };
+    let tera: &Tera = &templates.tera;
     let class_key = "templates/proto/class.tpl";
-    if let Some(content) = generate::get_template(class_key) {
-        tera.add_raw_template(class_key, &content)
-            .expect("Proto Failed to add class template");
-    }
     let enum_key = "templates/proto/enums.tpl";
-    if let Some(content) = generate::get_template(enum_key) {
-        tera.add_raw_template(enum_key, &content)
-            .expect("Proto Failed to add enum template");
-    }
 
     if common_protos.len() > 0 {
-        create_common_proto(&tera, &common_protos, build_setting)
+        create_common_proto(tera, &common_protos, build_setting)
     }
 
     let mut context: Context = Context::new();
-    context.insert("CLASS_NAME", &tree_data.file_name);
+    context.insert("CLASS_NAME", &table_data.file_name);
     context.insert("info", &proto_fields);
     context.insert("imports", &imports);
-    let rendered_result: Result<String, tera::Error> = match tree_data.gable_type {
+    context.insert("reserved", &reserved);
+    context.insert("allow_alias", &allow_alias);
+    let rendered_result: Result<String, tera::Error> = match table_data.gable_type {
         ESheetType::Normal | ESheetType::Localize | ESheetType::KV => {
             tera.render(class_key, &context)
         }
@@ -65,9 +70,9 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
 
     // 写入文件
     let proto_path: PathBuf = utils::get_absolute_path(&build_setting.script_path)
-        .join(format!("{}.proto", tree_data.file_name));
+        .join(format!("{}.proto", table_data.file_name));
 
-    let result: Result<(), Error> = std::fs::write(&proto_path, rendered);
+    let result: Result<bool, Error> = output::write(build_setting, &proto_path, rendered);
     if result.is_err() {
         log::error!(
             "Export [{}] failed: {}",
@@ -83,6 +88,55 @@ pub fn to(build_setting: &BuildSetting, tree_data: &TreeData) {
     }
 }
 
+/**
+ * 生成汇总所有表的数据库proto文件
+ * @param build_setting 构建设置
+ * @param datas 表名和表数据的映射
+*/
+pub fn to_bundle(build_setting: &BuildSetting, datas: &BTreeMap<String, Cow<TreeData>>) {
+    if build_setting.target_type != ETargetType::Protobuff || !build_setting.proto_bundle {
+        return;
+    }
+    let (imports, bundle_info, reserved) =
+        proto_field_info::transition_bundle(build_setting, datas);
+
+    let templates: Arc<LoadedTemplates> = match generate::get_templates(build_setting) {
+        Some(templates) => templates,
+        None => return,
+    };
+    let tera: &Tera = &templates.tera;
+    let class_key = "templates/proto/class.tpl";
+    let mut context: Context = Context::new();
+    context.insert("CLASS_NAME", constant::PROTO_BUNDLE_NAME);
+    context.insert("info", &bundle_info);
+    context.insert("imports", &imports);
+    context.insert("reserved", &reserved);
+    let rendered: String = match tera.render(class_key, &context) {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            log::error!("Template error: {}", e);
+            return;
+        }
+    };
+
+    let proto_path: PathBuf = utils::get_absolute_path(&build_setting.script_path)
+        .join(format!("{}.proto", constant::PROTO_BUNDLE_NAME));
+    if let Err(e) = output::write(build_setting, &proto_path, rendered) {
+        log::error!(
+            "Export [{}] failed: {} - {}",
+            build_setting.display_name,
+            proto_path.to_str().unwrap(),
+            e
+        );
+    } else {
+        log::info!(
+            "Export [{}] successful: {}",
+            build_setting.display_name,
+            proto_path.to_str().unwrap()
+        );
+    }
+}
+
 /**
  * 生成公共的proto文件
  * @param tera 模板
@@ -194,6 +248,7 @@ fn create_common_proto(tera: &Tera, common_protos: &Vec<&EDataType>, build_setti
         common_context.insert("CLASS_NAME", class_name);
         common_context.insert("info", &content_info);
         common_context.insert("imports", &Vec::<String>::new());
+        common_context.insert("reserved", &Vec::<ProtoRegistryField>::new());
 
         let rendered_result: Result<String, tera::Error> =
             tera.render("templates/proto/class.tpl", &common_context);
@@ -208,7 +263,7 @@ fn create_common_proto(tera: &Tera, common_protos: &Vec<&EDataType>, build_setti
         let target_path: PathBuf = utils::get_absolute_path(&build_setting.script_path)
             .join(format!("{}.proto", class_name));
 
-        let result: Result<(), Error> = std::fs::write(&target_path, rendered);
+        let result: Result<bool, Error> = output::write(build_setting, &target_path, rendered);
         if result.is_err() {
             log::error!(
                 "Export [{}] failed: {}",
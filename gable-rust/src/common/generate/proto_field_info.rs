@@ -1,10 +1,22 @@
 use crate::{
     common::{
         constant,
-        generate::generate::{GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem},
+        generate::{
+            generate::{GenerateFieldInfo, GenerateFieldItem, GenerateMainFieldItem},
+            proto_registry::{self, ProtoRegistryField},
+        },
+        naming,
+        setting::BuildSetting,
+    },
+    gui::datas::{
+        edata_type::EDataType,
+        esheet_type::ESheetType,
+        gables,
+        table_data::TableData,
+        tree_data::{FieldInfo, TreeData},
     },
-    gui::datas::{edata_type::EDataType, gables, tree_data::FieldInfo},
 };
+use std::{borrow::Cow, collections::BTreeMap};
 
 /**
  * 通用字段转换成Protobuff字段
@@ -87,29 +99,38 @@ pub fn transition_fields(
                 "repeated Vector4"
             }
             EDataType::Enum => {
-                let mut enum_name = "int32";
-                if !field.field_link.is_empty() {
-                    if isproto2 {
-                        gables::get_enum_cells(&field.field_link, |enum_datas| {
-                            for r_d in enum_datas.cells.iter() {
-                                if let Some(r_c) = r_d.get(constant::TABLE_ENUM_COL_FIELD) {
-                                    if !r_c.value.is_empty() {
-                                        field_extend = format!(" [default = {}]", r_c.value);
-                                        break;
-                                    }
-                                }
-                            }
-                        });
+                // 关联的枚举表存在时引用生成的枚举类型，否则退化为int32
+                let default_member: Option<Option<String>> =
+                    gables::get_enum_cells(&field.field_link, |enum_datas| {
+                        enum_datas.cells.iter().find_map(|r_d| {
+                            r_d.get(constant::TABLE_ENUM_COL_FIELD)
+                                .filter(|r_c| !r_c.value.is_empty())
+                                .map(|r_c| r_c.value.clone())
+                        })
+                    });
+                match default_member {
+                    Some(default_member) => {
+                        if let (true, Some(default_member)) = (isproto2, default_member) {
+                            field_extend = format!(" [default = {}]", default_member);
+                        }
+                        let enum_name: &str = match field.field_link.find("@") {
+                            Some(pos) => &field.field_link[pos + 1..],
+                            None => &field.field_link,
+                        };
+                        if !imports.iter().any(|item| item == enum_name) {
+                            imports.push(enum_name.to_string());
+                        }
+                        enum_name
+                    }
+                    None => {
+                        log::warn!(
+                            "Enum {} of field {} not found, exported as int32",
+                            field.field_link,
+                            field.field_name
+                        );
+                        "int32"
                     }
-                    if let Some(pos) = field.field_link.find("@") {
-                        enum_name = &field.field_link[pos + 1..];
-                    } else {
-                        enum_name = &field.field_link;
-                    };
-
-                    imports.push(enum_name.to_string());
                 }
-                enum_name
             }
         };
         let data_type = if field.field_type == EDataType::Enum {
@@ -150,3 +171,133 @@ pub fn transition_fields(
     };
     return (imports, generate_info, common_proto);
 }
+
+/**
+ * 枚举表转换成Protobuff枚举成员
+ * proto3要求第一个成员为0，没有0值成员时补充`枚举名_UNSPECIFIED = 0`，有则移动到首位；
+ * 多个成员使用相同的值时需要开启allow_alias；重复的成员名会被忽略
+ * @param table_data 枚举表数据，枚举成员不受关键字筛选
+ * @param isproto2 是否是版本2
+ * @return (枚举成员, 是否开启allow_alias)
+ */
+pub fn transition_enum(table_data: &TableData, isproto2: bool) -> (GenerateFieldInfo, bool) {
+    let mut fields: Vec<GenerateFieldItem> = Vec::new();
+    for field in table_data.field_info.fields.iter() {
+        if fields
+            .iter()
+            .any(|item| item.field_name == field.field_name)
+        {
+            log::error!(
+                "Duplicate enum member {} in {}",
+                field.field_name,
+                table_data.file_name
+            );
+            continue;
+        }
+        fields.push(GenerateFieldItem {
+            field_name: field.field_name.clone(),
+            field_type: String::new(),
+            field_desc: field.field_desc.clone(),
+            field_index: field.field_index,
+            field_extend: String::new(),
+            data_type: "enum".to_string(),
+        });
+    }
+    if !isproto2 {
+        match fields.iter().position(|item| item.field_index == 0) {
+            Some(pos) => {
+                let zero: GenerateFieldItem = fields.remove(pos);
+                fields.insert(0, zero);
+            }
+            None => fields.insert(
+                0,
+                GenerateFieldItem {
+                    field_name: format!("{}_UNSPECIFIED", table_data.file_name),
+                    field_type: String::new(),
+                    field_desc: String::new(),
+                    field_index: 0,
+                    field_extend: String::new(),
+                    data_type: "enum".to_string(),
+                },
+            ),
+        }
+    }
+    let allow_alias: bool = fields.iter().enumerate().any(|(index, item)| {
+        fields[..index]
+            .iter()
+            .any(|other| other.field_index == item.field_index)
+    });
+    let info: GenerateFieldInfo = GenerateFieldInfo {
+        primary_num: 0,
+        main_fields: vec![],
+        fields,
+    };
+    (info, allow_alias)
+}
+
+/**
+ * 生成汇总所有表的数据库消息字段
+ * 普通表（本地化表）为repeated字段，KV表为单个消息字段，字段编号记录在数据库消息的字段编号表中
+ * @param build_setting 构建设置
+ * @param datas 表名和表数据的映射
+ * @return (导入列表, 字段信息, 已删除的字段)，字段描述为对应的表名
+ */
+pub fn transition_bundle(
+    build_setting: &BuildSetting,
+    datas: &BTreeMap<String, Cow<TreeData>>,
+) -> (Vec<String>, GenerateFieldInfo, Vec<ProtoRegistryField>) {
+    let mut tables: Vec<&TreeData> = Vec::new();
+    for data in datas.values() {
+        if data.gable_type == ESheetType::Enum {
+            continue;
+        }
+        if data.file_name == constant::PROTO_BUNDLE_NAME {
+            log::error!(
+                "Table {} conflicts with the bundle message name",
+                data.file_name
+            );
+            continue;
+        }
+        if data.to_fields(&build_setting.keyword).is_none() {
+            continue;
+        }
+        tables.push(data);
+    }
+    tables.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+    let mut imports: Vec<String> = Vec::new();
+    let mut fields: Vec<GenerateFieldItem> = Vec::new();
+    // 编号表使用原始表名，不同构建设置的字段命名不影响编号
+    let mut all_fields: Vec<(String, String)> = Vec::new();
+    for data in tables {
+        let field_name: String = naming::convert(&data.file_name, build_setting.naming);
+        let field_type: String = if data.gable_type == ESheetType::KV {
+            data.file_name.clone()
+        } else {
+            format!("repeated {}", data.file_name)
+        };
+        all_fields.push((data.file_name.clone(), format!("{:?}", data.gable_type)));
+        imports.push(data.file_name.clone());
+        fields.push(GenerateFieldItem {
+            field_name,
+            field_type: field_type.clone(),
+            field_desc: data.file_name.clone(),
+            field_index: 0,
+            field_extend: String::new(),
+            data_type: field_type,
+        });
+    }
+    let mut info: GenerateFieldInfo = GenerateFieldInfo {
+        primary_num: 0,
+        main_fields: vec![],
+        fields,
+    };
+    let reserved: Vec<ProtoRegistryField> = proto_registry::apply_fields(
+        constant::PROTO_BUNDLE_NAME,
+        &all_fields,
+        &[],
+        &mut info,
+        |name| naming::convert(name, build_setting.naming),
+    );
+    (imports, info, reserved)
+}
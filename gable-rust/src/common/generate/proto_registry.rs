@@ -0,0 +1,523 @@
+use crate::{
+    common::{
+        constant, generate::generate::GenerateFieldInfo, naming, overlay, setting,
+        setting::BuildSetting,
+    },
+    gui::datas::{
+        esheet_type::ESheetType,
+        tree_data::{FieldInfo, TreeData},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+/**
+ * 读写字段编号表时持有的锁，避免数据导出和脚本生成同时修改同一个文件
+ */
+static REGISTRY_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/**
+ * 字段编号
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtoRegistryField {
+    /// 字段名称
+    pub field_name: String,
+    /// 字段编号
+    pub field_index: i32,
+    /// 字段类型，用于识别改名的列
+    #[serde(default)]
+    pub field_type: String,
+}
+
+/**
+ * 单个表的字段编号表，保存在工作目录下，需要随配置一起提交
+ * 字段名使用表格中的原始列名（覆盖数据和字段命名之前），不同构建设置共用同一份编号
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtoRegistry {
+    /// 当前字段，按列顺序排列
+    pub fields: Vec<ProtoRegistryField>,
+    /// 已删除字段，编号不再分配
+    pub reserved: Vec<ProtoRegistryField>,
+    /// 覆盖数据新增的字段，只在部分构建设置中存在，不会因缺失而被删除
+    pub overlays: Vec<ProtoRegistryField>,
+}
+
+/**
+ * 获取表的字段编号表路径
+ * @param table_name 表名
+ * @return 字段编号表路径
+ */
+pub fn get_registry_path(table_name: &str) -> PathBuf {
+    setting::get_workspace()
+        .join(constant::DIR_PROTO_REGISTRY)
+        .join(format!("{}.json", table_name))
+}
+
+/**
+ * 使用字段编号表为Protobuff字段分配稳定的编号
+ * 编号按原始列名匹配，移动列或修改字段命名不会改变编号；删除的字段编号会被保留，不再分配给新字段
+ * @param build_setting 构建设置，用于计算输出的字段名
+ * @param source 覆盖数据和字段命名之前的表数据
+ * @param info Protobuff字段信息，字段编号会被替换为编号表中的编号
+ * @return 已删除（保留）的字段
+ */
+pub fn apply(
+    build_setting: &BuildSetting,
+    source: &TreeData,
+    info: &mut GenerateFieldInfo,
+) -> Vec<ProtoRegistryField> {
+    if source.gable_type == ESheetType::Enum {
+        return Vec::new();
+    }
+    // 使用不区分平台的完整字段列表同步，避免关键字筛选掉的字段被当成删除
+    let all_fields: Vec<(String, String)> = match source.to_fields("") {
+        Some(all_fields) => to_registry_fields(&all_fields),
+        None => return Vec::new(),
+    };
+    // 覆盖数据新增的列单独记录，切换构建设置时不会被当成删除
+    let overlay_fields: Vec<(String, String)> = match overlay::patch(build_setting, source)
+        .ok()
+        .flatten()
+        .and_then(|(patched, _)| patched.to_fields(""))
+    {
+        Some(patched_fields) => to_registry_fields(&patched_fields)
+            .into_iter()
+            .filter(|(field_name, _)| !all_fields.iter().any(|(name, _)| name == field_name))
+            .collect(),
+        None => Vec::new(),
+    };
+    let aliases: HashMap<String, String> = naming::parse_aliases(&build_setting.field_aliases);
+    apply_fields(
+        &source.file_name,
+        &all_fields,
+        &overlay_fields,
+        info,
+        |field_name| {
+            naming::get_field_name(
+                &source.file_name,
+                field_name,
+                build_setting.naming,
+                &aliases,
+            )
+        },
+    )
+}
+
+/**
+ * 转换为编号表使用的字段列表（字段名，字段类型）
+ */
+fn to_registry_fields(field_info: &FieldInfo) -> Vec<(String, String)> {
+    field_info
+        .fields
+        .iter()
+        .map(|field| (field.field_name.clone(), format!("{:?}", field.field_type)))
+        .collect()
+}
+
+/**
+ * 使用指定名称的字段编号表为Protobuff字段分配稳定的编号
+ * @param registry_name 编号表名称，通常为消息名
+ * @param all_fields 完整的字段列表（原始字段名，字段类型），按列顺序排列
+ * @param overlay_fields 覆盖数据新增的字段列表（原始字段名，字段类型）
+ * @param info Protobuff字段信息，字段编号会被替换为编号表中的编号
+ * @param output_name 原始字段名转换为输出字段名的方法
+ * @return 已删除（保留）的字段
+ */
+pub fn apply_fields(
+    registry_name: &str,
+    all_fields: &[(String, String)],
+    overlay_fields: &[(String, String)],
+    info: &mut GenerateFieldInfo,
+    output_name: impl Fn(&str) -> String,
+) -> Vec<ProtoRegistryField> {
+    let _lock = REGISTRY_LOCK.lock().unwrap();
+    let registry_path: PathBuf = get_registry_path(registry_name);
+    let mut registry: ProtoRegistry = load_registry(&registry_path);
+    let before: ProtoRegistry = registry.clone();
+    sync_registry(registry_name, &mut registry, all_fields);
+    sync_overlays(&mut registry, overlay_fields);
+    if registry != before {
+        save_registry(&registry_path, &registry);
+    }
+
+    // 输出字段名 -> 编号
+    let numbers: HashMap<String, i32> = registry
+        .fields
+        .iter()
+        .chain(registry.overlays.iter())
+        .map(|item| (output_name(&item.field_name), item.field_index))
+        .collect();
+    for field in info.fields.iter_mut() {
+        match numbers.get(&field.field_name) {
+            Some(field_index) => field.field_index = *field_index,
+            None => log::error!(
+                "Field {} of {} is missing from the protobuf field registry",
+                field.field_name,
+                registry_name
+            ),
+        }
+    }
+    registry.reserved
+}
+
+/**
+ * 为覆盖数据新增的字段分配编号，已有编号的字段保持不变
+ */
+fn sync_overlays(registry: &mut ProtoRegistry, overlay_fields: &[(String, String)]) {
+    for (field_name, field_type) in overlay_fields.iter() {
+        if registry
+            .overlays
+            .iter()
+            .any(|item| item.field_name == *field_name)
+        {
+            continue;
+        }
+        let field_index: i32 = match registry
+            .reserved
+            .iter()
+            .position(|item| item.field_name == *field_name)
+        {
+            Some(pos) => registry.reserved.remove(pos).field_index,
+            None => get_next_index(registry),
+        };
+        registry.overlays.push(ProtoRegistryField {
+            field_name: field_name.clone(),
+            field_index,
+            field_type: field_type.clone(),
+        });
+    }
+}
+
+/**
+ * 获取下一个未使用的字段编号
+ */
+fn get_next_index(registry: &ProtoRegistry) -> i32 {
+    registry
+        .fields
+        .iter()
+        .chain(registry.reserved.iter())
+        .chain(registry.overlays.iter())
+        .map(|item| item.field_index)
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+/**
+ * 读取字段编号表，文件不存在时返回空表
+ */
+fn load_registry(path: &Path) -> ProtoRegistry {
+    if !path.exists() {
+        return ProtoRegistry::default();
+    }
+    let content: String = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            log::error!("Failed to read {}: {}", path.display(), e);
+            return ProtoRegistry::default();
+        }
+    };
+    match serde_json::from_str(&content) {
+        Ok(registry) => registry,
+        Err(e) => {
+            log::error!("Failed to parse {}: {}", path.display(), e);
+            ProtoRegistry::default()
+        }
+    }
+}
+
+/**
+ * 保存字段编号表
+ */
+fn save_registry(path: &Path, registry: &ProtoRegistry) {
+    let parent: &Path = match path.parent() {
+        Some(parent) => parent,
+        None => return,
+    };
+    if let Err(e) = fs::create_dir_all(parent) {
+        log::error!("Failed to create directory {}: {}", parent.display(), e);
+        return;
+    }
+    let content: String =
+        serde_json::to_string_pretty(registry).expect("JSON serialization failed");
+    if let Err(e) = fs::write(path, content) {
+        log::error!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+/**
+ * 按当前表头同步字段编号表
+ * 1. 同名字段保留原编号；曾被删除的字段恢复原编号
+ * 2. 其余新字段如果与被删除字段处于相同位置且类型相同，视为改名并沿用编号
+ * 3. 仍未匹配的新字段分配新编号，未匹配的旧字段移入保留列表
+ */
+fn sync_registry(table_name: &str, registry: &mut ProtoRegistry, all_fields: &[(String, String)]) {
+    let old_fields: Vec<ProtoRegistryField> = std::mem::take(&mut registry.fields);
+    let names: HashSet<&str> = all_fields
+        .iter()
+        .map(|(field_name, _)| field_name.as_str())
+        .collect();
+    let mut new_fields: Vec<Option<ProtoRegistryField>> = Vec::new();
+    for (field_name, field_type) in all_fields.iter() {
+        let matched: Option<i32> = old_fields
+            .iter()
+            .find(|item| item.field_name == *field_name)
+            .map(|item| item.field_index);
+        let matched: Option<i32> = match matched {
+            Some(field_index) => Some(field_index),
+            None => match registry
+                .overlays
+                .iter()
+                .position(|item| item.field_name == *field_name)
+            {
+                // 覆盖数据新增的列加入了基础表，沿用原编号
+                Some(pos) => Some(registry.overlays.remove(pos).field_index),
+                None => match registry
+                    .reserved
+                    .iter()
+                    .position(|item| item.field_name == *field_name)
+                {
+                    Some(pos) => {
+                        let item: ProtoRegistryField = registry.reserved.remove(pos);
+                        log::info!(
+                            "Field {} of {} restored with number {}",
+                            field_name,
+                            table_name,
+                            item.field_index
+                        );
+                        Some(item.field_index)
+                    }
+                    None => None,
+                },
+            },
+        };
+        new_fields.push(matched.map(|field_index| ProtoRegistryField {
+            field_name: field_name.clone(),
+            field_index,
+            field_type: field_type.clone(),
+        }));
+    }
+
+    // 未匹配的旧字段
+    let mut removed: Vec<(usize, ProtoRegistryField)> = old_fields
+        .into_iter()
+        .enumerate()
+        .filter(|(_, item)| !names.contains(item.field_name.as_str()))
+        .collect();
+    for (position, (field_name, field_type)) in all_fields.iter().enumerate() {
+        if new_fields[position].is_some() {
+            continue;
+        }
+        let renamed: Option<usize> = removed.iter().position(|(old_position, item)| {
+            *old_position == position && item.field_type == *field_type
+        });
+        if let Some(pos) = renamed {
+            let (_, item) = removed.remove(pos);
+            log::info!(
+                "Field {} of {} is treated as renamed from {} (number {})",
+                field_name,
+                table_name,
+                item.field_name,
+                item.field_index
+            );
+            new_fields[position] = Some(ProtoRegistryField {
+                field_name: field_name.clone(),
+                field_index: item.field_index,
+                field_type: field_type.clone(),
+            });
+        }
+    }
+    for (_, item) in removed {
+        log::warn!(
+            "Field {} of {} was removed, number {} is reserved",
+            item.field_name,
+            table_name,
+            item.field_index
+        );
+        registry.reserved.push(item);
+    }
+
+    // 仍未匹配的字段分配新编号
+    let mut next_index: i32 = new_fields
+        .iter()
+        .flatten()
+        .chain(registry.reserved.iter())
+        .chain(registry.overlays.iter())
+        .map(|item| item.field_index)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    for (position, (field_name, field_type)) in all_fields.iter().enumerate() {
+        let item: ProtoRegistryField = match new_fields[position].take() {
+            Some(item) => item,
+            None => {
+                let field_index: i32 = next_index;
+                next_index += 1;
+                ProtoRegistryField {
+                    field_name: field_name.clone(),
+                    field_index,
+                    field_type: field_type.clone(),
+                }
+            }
+        };
+        registry.fields.push(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_fields(fields: &[(&str, &str)]) -> Vec<(String, String)> {
+        fields
+            .iter()
+            .map(|(field_name, field_type)| (field_name.to_string(), field_type.to_string()))
+            .collect()
+    }
+
+    fn field(field_name: &str, field_index: i32, field_type: &str) -> ProtoRegistryField {
+        ProtoRegistryField {
+            field_name: field_name.to_string(),
+            field_index,
+            field_type: field_type.to_string(),
+        }
+    }
+
+    fn indexes(fields: &[ProtoRegistryField]) -> Vec<(&str, i32)> {
+        fields
+            .iter()
+            .map(|item| (item.field_name.as_str(), item.field_index))
+            .collect()
+    }
+
+    #[test]
+    fn existing_fields_keep_their_numbers() {
+        let mut registry: ProtoRegistry = ProtoRegistry::default();
+        sync_registry(
+            "Item",
+            &mut registry,
+            &to_fields(&[("id", "int"), ("name", "string")]),
+        );
+        assert_eq!(indexes(&registry.fields), vec![("id", 1), ("name", 2)]);
+
+        // 插入新列和调整列顺序不影响已有字段的编号
+        sync_registry(
+            "Item",
+            &mut registry,
+            &to_fields(&[("name", "string"), ("level", "int"), ("id", "int")]),
+        );
+        assert_eq!(
+            indexes(&registry.fields),
+            vec![("name", 2), ("level", 3), ("id", 1)]
+        );
+        assert!(registry.reserved.is_empty());
+    }
+
+    #[test]
+    fn deleted_fields_are_reserved_and_not_reused() {
+        let mut registry: ProtoRegistry = ProtoRegistry {
+            fields: vec![
+                field("id", 1, "int"),
+                field("hp", 2, "int"),
+                field("mp", 3, "int"),
+            ],
+            ..ProtoRegistry::default()
+        };
+        sync_registry(
+            "Item",
+            &mut registry,
+            &to_fields(&[("id", "int"), ("hp", "int")]),
+        );
+        assert_eq!(indexes(&registry.reserved), vec![("mp", 3)]);
+
+        // 新字段跳过保留的编号
+        sync_registry(
+            "Item",
+            &mut registry,
+            &to_fields(&[("id", "int"), ("hp", "int"), ("name", "string")]),
+        );
+        assert_eq!(
+            indexes(&registry.fields),
+            vec![("id", 1), ("hp", 2), ("name", 4)]
+        );
+
+        // 删除的字段加回来时恢复原编号
+        sync_registry(
+            "Item",
+            &mut registry,
+            &to_fields(&[
+                ("id", "int"),
+                ("hp", "int"),
+                ("name", "string"),
+                ("mp", "int"),
+            ]),
+        );
+        assert_eq!(
+            indexes(&registry.fields),
+            vec![("id", 1), ("hp", 2), ("name", 4), ("mp", 3)]
+        );
+        assert!(registry.reserved.is_empty());
+    }
+
+    #[test]
+    fn renamed_column_keeps_its_number() {
+        let mut registry: ProtoRegistry = ProtoRegistry {
+            fields: vec![
+                field("id", 1, "int"),
+                field("name", 2, "string"),
+                field("cd", 3, "int"),
+            ],
+            ..ProtoRegistry::default()
+        };
+        // 相同位置、相同类型的新字段视为改名
+        sync_registry(
+            "Item",
+            &mut registry,
+            &to_fields(&[("id", "int"), ("title", "string"), ("cd", "int")]),
+        );
+        assert_eq!(
+            indexes(&registry.fields),
+            vec![("id", 1), ("title", 2), ("cd", 3)]
+        );
+        assert!(registry.reserved.is_empty());
+
+        // 类型不同时不视为改名
+        sync_registry(
+            "Item",
+            &mut registry,
+            &to_fields(&[("id", "int"), ("caption", "int"), ("cd", "int")]),
+        );
+        assert_eq!(
+            indexes(&registry.fields),
+            vec![("id", 1), ("caption", 4), ("cd", 3)]
+        );
+        assert_eq!(indexes(&registry.reserved), vec![("title", 2)]);
+    }
+
+    #[test]
+    fn overlay_fields_keep_their_numbers_when_added_to_the_base() {
+        let mut registry: ProtoRegistry = ProtoRegistry {
+            fields: vec![field("id", 1, "int")],
+            overlays: vec![field("bonus", 2, "int")],
+            ..ProtoRegistry::default()
+        };
+        sync_registry(
+            "Item",
+            &mut registry,
+            &to_fields(&[("id", "int"), ("level", "int"), ("bonus", "int")]),
+        );
+        assert_eq!(
+            indexes(&registry.fields),
+            vec![("id", 1), ("level", 3), ("bonus", 2)]
+        );
+        assert!(registry.overlays.is_empty());
+    }
+}
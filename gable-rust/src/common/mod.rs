@@ -1,17 +1,31 @@
+pub mod build_cache;
 pub mod constant;
 pub mod excel_util;
 pub mod locales;
 pub mod localization;
+pub mod naming;
+pub mod output;
+pub mod overlay;
 pub mod res;
 pub mod setting;
 pub mod utils;
 pub mod convert {
     pub mod convert;
     pub mod convert_csv;
+    pub mod convert_custom;
     pub mod convert_json;
+    pub mod convert_json5;
+    pub mod convert_json_schema;
+    pub mod convert_lua;
+    pub mod convert_msgpack;
     pub mod convert_protobuff;
+    pub mod convert_sql;
+    pub mod convert_sqlite;
+    pub mod convert_toml;
     pub mod convert_xml;
+    pub mod convert_xml_schema;
     pub mod convert_yaml;
+    pub mod verify_protobuff;
 }
 
 pub mod generate {
@@ -28,4 +42,5 @@ pub mod generate {
     pub mod generate_rust;
     pub mod generate_typescript;
     pub mod proto_field_info;
+    pub mod proto_registry;
 }
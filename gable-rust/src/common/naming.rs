@@ -0,0 +1,289 @@
+use crate::{
+    common::{constant, setting::BuildSetting},
+    gui::datas::{
+        cell_data::CellData, enaming_type::ENamingType, esheet_type::ESheetType,
+        tree_data::TreeData,
+    },
+};
+use std::collections::HashMap;
+
+/**
+ * 按构建设置的命名规则和字段别名重命名表数据中的字段
+ * 导出数据和生成代码都从重命名后的字段行读取字段名，保证两者一致
+ * 多个字段重命名为相同的名称时，导出的数据会互相覆盖，生成的代码也无法编译，该表不能导出
+ * @param build_setting 构建设置
+ * @param tree_data 表数据
+ * @return 重命名后的表数据，不需要重命名时返回Ok(None)；字段重名时返回错误信息
+ */
+pub fn apply(
+    build_setting: &BuildSetting,
+    tree_data: &TreeData,
+) -> Result<Option<TreeData>, String> {
+    if tree_data.gable_type == ESheetType::Enum {
+        return Ok(None);
+    }
+    let aliases: HashMap<String, String> = parse_aliases(&build_setting.field_aliases);
+    if build_setting.naming == ENamingType::Original && aliases.is_empty() {
+        return Ok(None);
+    }
+    let mut renamed: TreeData = tree_data.clone();
+    let field_cells: Vec<&mut CellData> = match renamed.gable_type {
+        ESheetType::Normal | ESheetType::Localize => {
+            match renamed
+                .content
+                .heads
+                .get_mut(constant::TABLE_NORMAL_ROW_FIELD)
+            {
+                Some(row) => row.iter_mut().collect(),
+                None => return Ok(None),
+            }
+        }
+        ESheetType::KV => renamed
+            .content
+            .cells
+            .iter_mut()
+            .filter_map(|row| row.get_mut(constant::TABLE_KV_COL_FIELD))
+            .collect(),
+        ESheetType::Enum => return Ok(None),
+    };
+    // 新名称 -> 原字段名
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut duplicates: Vec<String> = Vec::new();
+    for cell in field_cells {
+        if !cell.verify_lawful() {
+            continue;
+        }
+        let is_main: bool = cell.value.contains("*");
+        let field_name: String = cell.value.replace("*", "");
+        let new_name: String = get_field_name(
+            &tree_data.file_name,
+            &field_name,
+            build_setting.naming,
+            &aliases,
+        );
+        match names.get(&new_name) {
+            Some(other) => duplicates.push(format!("{}, {} -> {}", other, field_name, new_name)),
+            None => {
+                names.insert(new_name.clone(), field_name.clone());
+            }
+        }
+        cell.value = if is_main {
+            format!("*{}", new_name)
+        } else {
+            new_name
+        };
+    }
+    if !duplicates.is_empty() {
+        return Err(format!(
+            "Fields of {} are renamed to duplicate names: {}",
+            tree_data.file_name,
+            duplicates.join(", ")
+        ));
+    }
+    Ok(Some(renamed))
+}
+
+/**
+ * 获取字段在目标平台中的名称，优先使用`表名.字段名`的别名，其次使用`字段名`的别名，最后按命名规则转换
+ * @param table_name 表名
+ * @param field_name 表格中的字段名
+ * @param naming 命名规则
+ * @param aliases 字段别名
+ * @return 目标平台中的字段名
+ */
+pub fn get_field_name(
+    table_name: &str,
+    field_name: &str,
+    naming: ENamingType,
+    aliases: &HashMap<String, String>,
+) -> String {
+    if let Some(alias) = aliases.get(&format!("{}.{}", table_name, field_name)) {
+        return alias.clone();
+    }
+    if let Some(alias) = aliases.get(field_name) {
+        return alias.clone();
+    }
+    convert(field_name, naming)
+}
+
+/**
+ * 解析字段别名，每行一个，格式为`字段名=别名`或`表名.字段名=别名`
+ * @param text 别名文本
+ * @return 字段名 -> 别名
+ */
+pub fn parse_aliases(text: &str) -> HashMap<String, String> {
+    let mut aliases: HashMap<String, String> = HashMap::new();
+    for line in text.lines() {
+        let line: &str = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (field_name, alias) = match line.split_once('=') {
+            Some((field_name, alias)) => (field_name.trim(), alias.trim()),
+            None => {
+                log::warn!("Invalid field alias: {}", line);
+                continue;
+            }
+        };
+        if field_name.is_empty() || alias.is_empty() {
+            log::warn!("Invalid field alias: {}", line);
+            continue;
+        }
+        aliases.insert(field_name.to_string(), alias.to_string());
+    }
+    aliases
+}
+
+/**
+ * 按命名规则转换名称
+ * @param name 原始名称
+ * @param naming 命名规则
+ * @return 转换后的名称
+ */
+pub fn convert(name: &str, naming: ENamingType) -> String {
+    if naming == ENamingType::Original {
+        return name.to_string();
+    }
+    let words: Vec<String> = split_words(name);
+    match naming {
+        ENamingType::Original => name.to_string(),
+        ENamingType::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                if index == 0 {
+                    word.to_lowercase()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect(),
+        ENamingType::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+        ENamingType::Snake => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("_"),
+        ENamingType::Kebab => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("-"),
+    }
+}
+
+/**
+ * 将名称拆分为单词，支持下划线、中划线、空格分隔以及大小写边界（如`itemID`、`HTTPCode`）
+ * @param name 名称
+ * @return 单词列表
+ */
+fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev: char = chars[index - 1];
+            let next_lower: bool = chars.get(index + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(*c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/**
+ * 首字母大写，其余字母小写
+ */
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::datas::gable_data::GableData;
+
+    fn normal_table(fields: &[&str]) -> TreeData {
+        let mut heads: Vec<Vec<CellData>> = vec![Vec::new(); constant::TABLE_NORMAL_ROW_TOTAL];
+        heads[constant::TABLE_NORMAL_ROW_FIELD] = fields
+            .iter()
+            .map(|field| CellData::new(field.to_string(), None, None))
+            .collect();
+        TreeData {
+            gable_type: ESheetType::Normal,
+            file_name: "Item".to_string(),
+            folder: String::new(),
+            content: GableData {
+                heads,
+                cells: Vec::new(),
+            },
+        }
+    }
+
+    fn field_names(tree_data: &TreeData) -> Vec<String> {
+        tree_data.content.heads[constant::TABLE_NORMAL_ROW_FIELD]
+            .iter()
+            .map(|cell| cell.value.clone())
+            .collect()
+    }
+
+    #[test]
+    fn renames_fields_and_keeps_primary_marker() {
+        let build_setting: BuildSetting = BuildSetting {
+            naming: ENamingType::Camel,
+            field_aliases: "Item.max_count=limit".to_string(),
+            ..BuildSetting::default()
+        };
+        let renamed: TreeData = apply(&build_setting, &normal_table(&["*item_id", "max_count"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(field_names(&renamed), vec!["*itemId", "limit"]);
+    }
+
+    #[test]
+    fn naming_collision_fails() {
+        let build_setting: BuildSetting = BuildSetting {
+            naming: ENamingType::Camel,
+            ..BuildSetting::default()
+        };
+        let result: Result<Option<TreeData>, String> =
+            apply(&build_setting, &normal_table(&["*id", "item_id", "itemId"]));
+        assert_eq!(
+            result.err(),
+            Some(
+                "Fields of Item are renamed to duplicate names: item_id, itemId -> itemId"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn alias_collision_fails() {
+        let build_setting: BuildSetting = BuildSetting {
+            field_aliases: "name=title\nItem.desc=title".to_string(),
+            ..BuildSetting::default()
+        };
+        let result: Result<Option<TreeData>, String> =
+            apply(&build_setting, &normal_table(&["*id", "name", "desc"]));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    common::{build_cache, setting::BuildSetting, utils},
+    gui::datas::etarget_type::ETargetType,
+};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::Error,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+/**
+ * 每个构建设置的输出统计，key为构建设置标识，修改显示名字不影响统计
+ */
+static OUTPUT_STATS: LazyLock<Mutex<BTreeMap<String, OutputStats>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+thread_local! {
+    // 当前线程正在收集的输出文件，为None时不收集
+    static PRODUCED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/**
+ * 输出文件统计
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputStats {
+    /// 写入的文件数量
+    pub written: usize,
+    /// 内容没有变化而跳过写入的文件数量
+    pub unchanged: usize,
+    /// 删除的文件数量
+    pub removed: usize,
+}
+
+/**
+ * 是否按工作空间的目录结构输出
+ * SQLite的所有表写入同一个数据库，Protobuff的proto文件按文件名导入，都保持平铺输出
+ * @param build_setting 构建设置
+ */
+pub fn is_mirror_folders(build_setting: &BuildSetting) -> bool {
+    build_setting.mirror_folders
+        && build_setting.target_type != ETargetType::SQLite
+        && build_setting.target_type != ETargetType::Protobuff
+}
+
+/**
+ * 获取表的输出名称，用于检查重名和记录构建缓存
+ * 按目录结构输出时包含所在目录，如`items/Weapon`，否则只有表名
+ * @param build_setting 构建设置
+ * @param folder 表所在的目录
+ * @param table_name 表名
+ */
+pub fn get_table_key(build_setting: &BuildSetting, folder: &str, table_name: &str) -> String {
+    if is_mirror_folders(build_setting) && !folder.is_empty() {
+        format!("{}/{}", folder, table_name)
+    } else {
+        table_name.to_string()
+    }
+}
+
+/**
+ * 获取表的输出目录，按目录结构输出时为根目录下与工作空间相同的子目录
+ * @param build_setting 构建设置
+ * @param root 输出根目录，target_path或script_path
+ * @param folder 表所在的目录
+ */
+pub fn get_table_dir(build_setting: &BuildSetting, root: &Path, folder: &str) -> PathBuf {
+    let root: PathBuf = utils::get_absolute_path(root);
+    if is_mirror_folders(build_setting) && !folder.is_empty() {
+        root.join(folder)
+    } else {
+        root
+    }
+}
+
+/**
+ * 写入输出文件，内容与已有文件相同时不写入，保留文件的修改时间，避免引擎和构建工具重新编译
+ * @param build_setting 构建设置
+ * @param path 文件路径
+ * @param contents 文件内容
+ * @return 是否写入了文件
+ */
+pub fn write(
+    build_setting: &BuildSetting,
+    path: &Path,
+    contents: impl AsRef<[u8]>,
+) -> Result<bool, Error> {
+    let contents: &[u8] = contents.as_ref();
+    let unchanged: bool = fs::metadata(path).is_ok_and(|meta| meta.len() == contents.len() as u64)
+        && fs::read(path).is_ok_and(|existing| existing == contents);
+    if !unchanged {
+        if let Some(parent) = path.parent() {
+            // 按目录结构输出时子目录可能还不存在
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+    }
+    record(build_setting, path, !unchanged);
+    Ok(!unchanged)
+}
+
+/**
+ * 删除不再输出的文件，文件不存在时忽略
+ * @param build_setting 构建设置
+ * @param path 文件路径
+ */
+pub fn remove(build_setting: &BuildSetting, path: &Path) {
+    if !path.is_file() {
+        return;
+    }
+    match fs::remove_file(path) {
+        Ok(()) => {
+            log::info!(
+                "Export [{}] removed: {}",
+                build_setting.display_name,
+                path.to_str().unwrap()
+            );
+            record_removed(build_setting);
+        }
+        Err(e) => log::error!("Failed to remove {}: {}", path.to_str().unwrap(), e),
+    }
+}
+
+/**
+ * 记录一次删除，没有通过remove删除的输出（如数据库中的数据表）需要调用
+ * @param build_setting 构建设置
+ */
+pub fn record_removed(build_setting: &BuildSetting) {
+    let mut stats = OUTPUT_STATS.lock().unwrap();
+    stats.entry(build_setting.id.clone()).or_default().removed += 1;
+}
+
+/**
+ * 记录一次输出，没有通过write写入的文件（如数据库中的数据表）也需要调用
+ * @param build_setting 构建设置
+ * @param path 文件路径
+ * @param written 是否写入，false表示内容没有变化
+ */
+pub fn record(build_setting: &BuildSetting, path: &Path, written: bool) {
+    PRODUCED.with(|produced| {
+        if let Some(produced) = produced.borrow_mut().as_mut() {
+            produced.push(path.to_string_lossy().to_string());
+        }
+    });
+    let mut stats = OUTPUT_STATS.lock().unwrap();
+    let stats: &mut OutputStats = stats.entry(build_setting.id.clone()).or_default();
+    if written {
+        stats.written += 1;
+    } else {
+        stats.unchanged += 1;
+    }
+}
+
+/**
+ * 输出构建设置本次构建的文件统计，并清空统计
+ * @param build_setting 构建设置
+ * @param action 构建类型，如Export、Generate
+ */
+pub fn report(build_setting: &BuildSetting, action: &str) {
+    let stats: OutputStats = OUTPUT_STATS
+        .lock()
+        .unwrap()
+        .remove(&build_setting.id)
+        .unwrap_or_default();
+    log::info!(
+        "{} [{}] files: {} written, {} unchanged, {} removed",
+        action,
+        build_setting.display_name,
+        stats.written,
+        stats.unchanged,
+        stats.removed
+    );
+}
+
+/**
+ * 收集f执行期间当前线程输出的文件，包括内容没有变化的文件
+ * @param f 输出文件的操作
+ * @return (f的返回值, 输出的文件)
+ */
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<String>) {
+    let outer: Option<Vec<String>> =
+        PRODUCED.with(|produced| produced.borrow_mut().replace(Vec::new()));
+    let result: R = f();
+    let produced: Vec<String> = PRODUCED
+        .with(|produced| std::mem::replace(&mut *produced.borrow_mut(), outer))
+        .unwrap_or_default();
+    (result, produced)
+}
+
+/**
+ * 清理过期文件：构建前记录的输出文件中，本次构建后不再由任何表输出的文件
+ * 构建设置开启了stale_dry_run时只列出不删除
+ * @param scope 缓存范围
+ * @param build_setting 构建设置
+ * @param previous 构建前记录的输出文件
+ */
+pub fn clean_stale(scope: &str, build_setting: &BuildSetting, previous: &BTreeSet<String>) {
+    build_cache::set_stale(scope, build_setting, Vec::new());
+    let current: BTreeSet<String> = build_cache::get_outputs(scope, build_setting);
+    let stale: Vec<String> = previous
+        .difference(&current)
+        .filter(|path| Path::new(path).is_file())
+        .cloned()
+        .collect();
+    if build_setting.stale_dry_run {
+        for path in stale.iter() {
+            log::warn!(
+                "Stale output [{}] (dry run): {}",
+                build_setting.display_name,
+                path
+            );
+        }
+        build_cache::set_stale(scope, build_setting, stale);
+    } else {
+        for path in stale.iter() {
+            remove(build_setting, Path::new(path));
+        }
+    }
+}
@@ -0,0 +1,476 @@
+use crate::{
+    common::{constant, excel_util, setting, setting::BuildSetting, utils},
+    gui::datas::{
+        cell_data::CellData, esheet_type::ESheetType, gable_data::GableData, tree_data::TreeData,
+    },
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Error,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+/**
+ * 覆盖目录下的表，key为表名，同一个表有多个覆盖表时value为错误信息
+ */
+type OverlaySheets = HashMap<String, Result<GableData, String>>;
+
+/**
+ * 已加载的覆盖数据，key为覆盖名称
+ */
+static OVERLAYS: LazyLock<Mutex<HashMap<String, OverlaySheets>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/**
+ * 清空已加载的覆盖数据，每次批量构建前调用，保证读取到最新的覆盖表
+ */
+pub fn clear() {
+    let mut overlays = OVERLAYS.lock().unwrap();
+    overlays.clear();
+}
+
+/**
+ * 获取覆盖目录
+ * @param overlay 覆盖名称
+ * @return 覆盖目录
+ */
+pub fn get_overlay_path(overlay: &str) -> PathBuf {
+    setting::get_workspace()
+        .join(constant::DIR_OVERLAYS)
+        .join(overlay)
+}
+
+/**
+ * 将构建设置选中的覆盖数据应用到表数据上
+ * @param build_setting 构建设置
+ * @param tree_data 基础表数据
+ * @return 覆盖后的表数据，没有对应的覆盖表时返回None；覆盖表重复或缺少主键时返回错误，该表不能导出
+ */
+pub fn apply(
+    build_setting: &BuildSetting,
+    tree_data: &TreeData,
+) -> Result<Option<TreeData>, String> {
+    let (patched, orphans) = match patch(build_setting, tree_data)? {
+        Some(patched) => patched,
+        None => return Ok(None),
+    };
+    for key in orphans.iter() {
+        log::warn!(
+            "Overlay [{}] row [{}] of {} no longer matches a base row",
+            build_setting.overlay,
+            key,
+            tree_data.file_name
+        );
+    }
+    log::info!(
+        "Overlay [{}] applied to {} ({} unmatched rows)",
+        build_setting.overlay,
+        tree_data.file_name,
+        orphans.len()
+    );
+    Ok(Some(patched))
+}
+
+/**
+ * 将构建设置选中的覆盖数据应用到表数据上，不输出覆盖结果日志
+ * @param build_setting 构建设置
+ * @param tree_data 基础表数据
+ * @return 覆盖后的表数据和未匹配到基础行的覆盖行主键列表，没有对应的覆盖表时返回None；覆盖表重复或缺少主键时返回错误
+ */
+pub fn patch(
+    build_setting: &BuildSetting,
+    tree_data: &TreeData,
+) -> Result<Option<(TreeData, Vec<String>)>, String> {
+    if build_setting.overlay.is_empty() || tree_data.gable_type == ESheetType::Enum {
+        return Ok(None);
+    }
+    let mut overlays = OVERLAYS.lock().unwrap();
+    let sheets: &OverlaySheets = overlays
+        .entry(build_setting.overlay.clone())
+        .or_insert_with(|| load_overlay(&build_setting.overlay));
+    let overlay_data: &GableData = match sheets.get(&tree_data.file_name) {
+        Some(Ok(overlay_data)) => overlay_data,
+        Some(Err(e)) => return Err(e.clone()),
+        None => return Ok(None),
+    };
+    let mut patched: TreeData = tree_data.clone();
+    let orphans: Vec<String> = match tree_data.gable_type {
+        ESheetType::Normal | ESheetType::Localize => {
+            patch_normal_data(&mut patched.content, overlay_data).map_err(|e| {
+                format!(
+                    "Overlay [{}] cannot be applied to {}: {}",
+                    build_setting.overlay, tree_data.file_name, e
+                )
+            })?
+        }
+        ESheetType::KV => patch_kv_data(&mut patched.content, overlay_data),
+        ESheetType::Enum => return Ok(None),
+    };
+    Ok(Some((patched, orphans)))
+}
+
+/**
+ * 读取覆盖目录下的所有gable文件
+ * @param overlay 覆盖名称
+ * @return 表名和表数据的映射，同一个表有多个覆盖表时为错误信息
+ */
+fn load_overlay(overlay: &str) -> OverlaySheets {
+    let mut sheets: OverlaySheets = HashMap::new();
+    let overlay_path: PathBuf = get_overlay_path(overlay);
+    if !overlay_path.is_dir() {
+        log::error!(
+            "Overlay directory does not exist: {}",
+            overlay_path.to_string_lossy()
+        );
+        return sheets;
+    }
+    let mut files: BTreeMap<String, Vec<(PathBuf, GableData)>> = BTreeMap::new();
+    collect_overlay_files(&overlay_path, &mut files);
+    for (sheet_name, mut datas) in files {
+        let sheet: Result<GableData, String> = if datas.len() == 1 {
+            Ok(datas.remove(0).1)
+        } else {
+            // 重复的覆盖表无法确定使用哪一个，对应的表不能导出
+            let paths: Vec<String> = datas
+                .iter()
+                .map(|(path, _)| path.to_string_lossy().to_string())
+                .collect();
+            Err(format!(
+                "Overlay [{}] has duplicate sheets for {}: {}",
+                overlay,
+                sheet_name,
+                paths.join(", ")
+            ))
+        };
+        sheets.insert(sheet_name, sheet);
+    }
+    sheets
+}
+
+/**
+ * 递归收集覆盖目录下的gable文件，按文件名排序，保证每次读取的顺序相同
+ * @param path 目录
+ * @param files 表名和该表的覆盖文件列表
+ */
+fn collect_overlay_files(path: &Path, files: &mut BTreeMap<String, Vec<(PathBuf, GableData)>>) {
+    let mut entries: Vec<fs::DirEntry> = match fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|e: Result<fs::DirEntry, Error>| e.ok())
+            .collect(),
+        Err(e) => {
+            log::error!("Unable to read the directory: {} - {}", path.display(), e);
+            return;
+        }
+    };
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let entry_path: PathBuf = entry.path();
+        if entry_path.is_dir() {
+            collect_overlay_files(&entry_path, files);
+            continue;
+        }
+        let entry_name: String = entry.file_name().to_string_lossy().to_string();
+        let (excel_name, sheet_name) = match utils::parse_gable_filename(&entry_name) {
+            Some(names) => names,
+            None => continue,
+        };
+        let sheet_name: String = sheet_name.unwrap_or(excel_name);
+        let gable_data: GableData = match excel_util::read_gable_file(&entry_path.to_string_lossy())
+        {
+            Some(gable_data) => gable_data,
+            None => continue,
+        };
+        files
+            .entry(sheet_name)
+            .or_default()
+            .push((entry_path, gable_data));
+    }
+}
+
+/**
+ * 获取单元格，不存在时返回空字符串
+ */
+fn cell_value(row: &[CellData], col_index: usize) -> String {
+    row.get(col_index)
+        .map(|cell| cell.value.clone())
+        .unwrap_or_default()
+}
+
+/**
+ * 设置单元格，行长度不足时自动补齐
+ */
+fn set_cell(row: &mut Vec<CellData>, col_index: usize, cell: CellData) {
+    if row.len() <= col_index {
+        row.resize(col_index + 1, CellData::new(String::new(), None, None));
+    }
+    row[col_index] = cell;
+}
+
+/**
+ * 按主键覆盖普通表（本地化表）数据
+ * @param base 基础表数据
+ * @param overlay 覆盖表数据
+ * @return 未匹配到基础行的覆盖行主键列表，覆盖表缺少主键或基础表没有主键时返回错误
+ */
+fn patch_normal_data(base: &mut GableData, overlay: &GableData) -> Result<Vec<String>, String> {
+    // 字段名 -> 列序号
+    let base_cols: HashMap<String, usize> = field_columns(base);
+    let overlay_cols: HashMap<String, usize> = field_columns(overlay);
+    let base_fields: Option<&Vec<CellData>> = base.heads.get(constant::TABLE_NORMAL_ROW_FIELD);
+    let mut primary_cols: Vec<(usize, usize)> = Vec::new();
+    if let Some(base_fields) = base_fields {
+        for (col_index, cell) in base_fields.iter().enumerate() {
+            if !cell.value.contains("*") {
+                continue;
+            }
+            let field_name: String = cell.value.replace("*", "");
+            if let Some(overlay_col) = overlay_cols.get(&field_name) {
+                primary_cols.push((col_index, *overlay_col));
+            } else {
+                return Err(format!(
+                    "the overlay is missing the primary key {}",
+                    field_name
+                ));
+            }
+        }
+    }
+    if primary_cols.is_empty() {
+        return Err("the base table has no primary key".to_string());
+    }
+
+    // 覆盖表中新增的列追加到基础表末尾
+    let mut column_map: Vec<(usize, usize)> = Vec::new();
+    let mut overlay_fields: Vec<(&String, &usize)> = overlay_cols.iter().collect();
+    overlay_fields.sort_by_key(|(_, col_index)| **col_index);
+    for (field_name, overlay_col) in overlay_fields {
+        if primary_cols.iter().any(|(_, o)| o == overlay_col) {
+            continue;
+        }
+        let base_col: usize = if let Some(base_col) = base_cols.get(field_name) {
+            *base_col
+        } else {
+            let new_col: usize = base.get_max_col();
+            for (row_index, overlay_row) in overlay.heads.iter().enumerate() {
+                while base.heads.len() <= row_index {
+                    base.heads.push(Vec::new());
+                }
+                if let Some(cell) = overlay_row.get(*overlay_col) {
+                    set_cell(&mut base.heads[row_index], new_col, cell.clone());
+                }
+            }
+            new_col
+        };
+        column_map.push((base_col, *overlay_col));
+    }
+
+    // 主键 -> 基础表行序号
+    let mut base_rows: HashMap<Vec<String>, usize> = HashMap::new();
+    for (row_index, row_data) in base.cells.iter().enumerate() {
+        let key: Vec<String> = primary_cols
+            .iter()
+            .map(|(base_col, _)| cell_value(row_data, *base_col))
+            .collect();
+        if key.iter().any(|v| v.is_empty()) {
+            continue;
+        }
+        base_rows.entry(key).or_insert(row_index);
+    }
+
+    let mut orphans: Vec<String> = Vec::new();
+    for overlay_row in overlay.cells.iter() {
+        let key: Vec<String> = primary_cols
+            .iter()
+            .map(|(_, overlay_col)| cell_value(overlay_row, *overlay_col))
+            .collect();
+        if key.iter().any(|v| v.is_empty()) {
+            continue;
+        }
+        let row_index: usize = if let Some(row_index) = base_rows.get(&key) {
+            *row_index
+        } else {
+            orphans.push(key.join(","));
+            continue;
+        };
+        for (base_col, overlay_col) in column_map.iter() {
+            if let Some(cell) = overlay_row.get(*overlay_col) {
+                if cell.value.is_empty() {
+                    continue;
+                }
+                set_cell(&mut base.cells[row_index], *base_col, cell.clone());
+            }
+        }
+    }
+    Ok(orphans)
+}
+
+/**
+ * 按键覆盖KV表数据
+ * @param base 基础表数据
+ * @param overlay 覆盖表数据
+ * @return 未匹配到基础行的覆盖行键列表
+ */
+fn patch_kv_data(base: &mut GableData, overlay: &GableData) -> Vec<String> {
+    let mut base_rows: HashMap<String, usize> = HashMap::new();
+    for (row_index, row_data) in base.cells.iter().enumerate() {
+        let key: String = cell_value(row_data, constant::TABLE_KV_COL_FIELD);
+        if !key.is_empty() {
+            base_rows.entry(key).or_insert(row_index);
+        }
+    }
+    let mut orphans: Vec<String> = Vec::new();
+    for overlay_row in overlay.cells.iter() {
+        let key: String = cell_value(overlay_row, constant::TABLE_KV_COL_FIELD);
+        if key.is_empty() {
+            continue;
+        }
+        let row_index: usize = if let Some(row_index) = base_rows.get(&key) {
+            *row_index
+        } else {
+            orphans.push(key);
+            continue;
+        };
+        for (col_index, cell) in overlay_row.iter().enumerate() {
+            if col_index == constant::TABLE_KV_COL_FIELD || cell.value.is_empty() {
+                continue;
+            }
+            set_cell(&mut base.cells[row_index], col_index, cell.clone());
+        }
+    }
+    orphans
+}
+
+/**
+ * 获取字段名和列序号的映射
+ * @param data 表数据
+ * @return 字段名(去掉主键标识) -> 列序号
+ */
+fn field_columns(data: &GableData) -> HashMap<String, usize> {
+    let mut columns: HashMap<String, usize> = HashMap::new();
+    if let Some(field_row) = data.heads.get(constant::TABLE_NORMAL_ROW_FIELD) {
+        for (col_index, cell) in field_row.iter().enumerate() {
+            if !cell.verify_lawful() {
+                continue;
+            }
+            columns
+                .entry(cell.value.replace("*", ""))
+                .or_insert(col_index);
+        }
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_row(values: &[&str]) -> Vec<CellData> {
+        values
+            .iter()
+            .map(|value| CellData::new(value.to_string(), None, None))
+            .collect()
+    }
+
+    /**
+     * 普通表数据，表头只填写字段名行
+     */
+    fn normal_data(fields: &[&str], rows: &[&[&str]]) -> GableData {
+        let mut heads: Vec<Vec<CellData>> = vec![Vec::new(); constant::TABLE_NORMAL_ROW_TOTAL];
+        heads[constant::TABLE_NORMAL_ROW_FIELD] = to_row(fields);
+        GableData {
+            heads,
+            cells: rows.iter().map(|row| to_row(row)).collect(),
+        }
+    }
+
+    fn values(data: &GableData) -> Vec<Vec<String>> {
+        data.cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.value.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn overlay_without_primary_key_fails() {
+        let mut base: GableData = normal_data(&["*id", "name"], &[&["1", "a"]]);
+        let overlay: GableData = normal_data(&["name"], &[&["b"]]);
+        let result: Result<Vec<String>, String> = patch_normal_data(&mut base, &overlay);
+        assert_eq!(
+            result,
+            Err("the overlay is missing the primary key id".to_string())
+        );
+        assert_eq!(values(&base), vec![vec!["1", "a"]]);
+    }
+
+    #[test]
+    fn base_without_primary_key_fails() {
+        let mut base: GableData = normal_data(&["id", "name"], &[&["1", "a"]]);
+        let overlay: GableData = normal_data(&["*id", "name"], &[&["1", "b"]]);
+        assert!(patch_normal_data(&mut base, &overlay).is_err());
+    }
+
+    #[test]
+    fn empty_overlay_cells_keep_base_values() {
+        let mut base: GableData = normal_data(
+            &["*id", "name", "level"],
+            &[&["1", "a", "10"], &["2", "b", "20"]],
+        );
+        let overlay: GableData = normal_data(
+            &["*id", "level", "name"],
+            &[
+                &["1", "", "A"],
+                &["2", "25", ""],
+                &["", "99", "X"],
+                &["3", "30", "C"],
+            ],
+        );
+        let orphans: Vec<String> = patch_normal_data(&mut base, &overlay).unwrap();
+        assert_eq!(orphans, vec!["3"]);
+        assert_eq!(
+            values(&base),
+            vec![vec!["1", "A", "10"], vec!["2", "b", "25"]]
+        );
+    }
+
+    #[test]
+    fn overlay_columns_missing_from_base_are_appended() {
+        let mut base: GableData = normal_data(&["*id", "name"], &[&["1", "a"], &["2", "b"]]);
+        let overlay: GableData = normal_data(&["*id", "extra"], &[&["2", "x"]]);
+        let orphans: Vec<String> = patch_normal_data(&mut base, &overlay).unwrap();
+        assert!(orphans.is_empty());
+        assert_eq!(
+            base.heads[constant::TABLE_NORMAL_ROW_FIELD][2].value,
+            "extra"
+        );
+        assert_eq!(values(&base), vec![vec!["1", "a"], vec!["2", "b", "x"]]);
+    }
+
+    #[test]
+    fn kv_overlay_patches_values_by_key() {
+        let mut base: GableData = GableData {
+            heads: Vec::new(),
+            cells: vec![
+                to_row(&["hp", "int", "c", "", "100", "health"]),
+                to_row(&["mp", "int", "c", "", "50", "mana"]),
+            ],
+        };
+        let overlay: GableData = GableData {
+            heads: Vec::new(),
+            cells: vec![
+                to_row(&["hp", "", "", "", "200", ""]),
+                to_row(&["", "", "", "", "1", ""]),
+                to_row(&["xp", "int", "c", "", "5", ""]),
+            ],
+        };
+        let orphans: Vec<String> = patch_kv_data(&mut base, &overlay);
+        assert_eq!(orphans, vec!["xp"]);
+        assert_eq!(
+            values(&base),
+            vec![
+                vec!["hp", "int", "c", "", "200", "health"],
+                vec!["mp", "int", "c", "", "50", "mana"]
+            ]
+        );
+    }
+}
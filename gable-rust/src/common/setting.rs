@@ -1,9 +1,16 @@
 use crate::common::locales::ELocalizationType;
 use crate::common::{constant, utils};
 use crate::gui::datas::esheet_type::ESheetType;
-use crate::gui::datas::{edevelop_type::EDevelopType, etarget_type::ETargetType};
+use crate::gui::datas::{
+    ecsv_array_type::ECsvArrayType, ecsv_delimiter_type::ECsvDelimiterType,
+    edevelop_type::EDevelopType, ejson_layout_type::EJsonLayoutType,
+    eline_ending_type::ELineEndingType, emsgpack_layout_type::EMsgPackLayoutType,
+    enaming_type::ENamingType, esql_dialect_type::ESqlDialectType, etarget_type::ETargetType,
+    exml_layout_type::EXmlLayoutType, eyaml_style_type::EYamlStyleType,
+};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 use std::{fs, io};
@@ -18,12 +25,56 @@ pub struct BuildSetting {
     pub dev: EDevelopType,
     /// 显示名字
     pub display_name: String,
+    /// 唯一标识，修改显示名字后保持不变，用于记录构建缓存
+    pub id: String,
     /// 关键字
     pub keyword: String,
     /// 构建目标类型
     pub target_type: ETargetType,
+    /// csv分隔符
+    pub csv_delimiter: ECsvDelimiterType,
+    /// csv是否写入UTF-8 BOM，Excel打开时才能正确识别编码
+    pub csv_bom: bool,
+    /// csv换行符
+    pub csv_line_ending: ELineEndingType,
+    /// csv是否写入描述行（仅普通表和本地化表）
+    pub csv_desc_row: bool,
+    /// csv是否写入类型行（KV表为类型列）
+    pub csv_type_row: bool,
+    /// csv单元格中数组和向量的写法
+    pub csv_array: ECsvArrayType,
+    /// json数据布局
+    pub json_layout: EJsonLayoutType,
+    /// json是否压缩输出
+    pub json_minify: bool,
+    /// 是否同时生成json对应的JSON Schema
+    pub json_schema: bool,
     // 是否是protobuff 2版本
     pub is_proto_2: bool,
+    /// 生成proto后是否校验导出的protobuff二进制数据
+    pub verify_protobuf: bool,
+    /// 是否额外生成汇总所有表的数据库proto和二进制文件
+    pub proto_bundle: bool,
+    /// MessagePack数据布局
+    pub msgpack_layout: EMsgPackLayoutType,
+    /// YAML输出风格
+    pub yaml_style: EYamlStyleType,
+    /// YAML是否将字段描述写为注释
+    pub yaml_comments: bool,
+    /// xml数据布局
+    pub xml_layout: EXmlLayoutType,
+    /// 是否同时生成xml对应的XSD
+    pub xml_schema: bool,
+    /// SQL脚本方言
+    pub sql_dialect: ESqlDialectType,
+    /// Lua数据是否按主键组织，否则导出为数组
+    pub lua_keyed: bool,
+    /// Lua数据是否使用只读元表，长度和遍历依赖`__len`、`__pairs`元方法，需要Lua 5.2及以上版本，Lua 5.1和LuaJIT中`#`和`pairs`得到的是空表
+    pub lua_readonly: bool,
+    /// 自定义数据模板文件，导出类型为Custom时使用
+    pub data_template: PathBuf,
+    /// 自定义数据文件扩展名
+    pub data_extension: String,
     // 是否自定义模板
     pub is_custom: bool,
     // 自定义模板路径
@@ -32,6 +83,18 @@ pub struct BuildSetting {
     pub postprocessing: String,
     /// 构建目标路径，相对路径
     pub target_path: PathBuf,
+    /// 覆盖数据名称（overlays目录下的子目录），为空时不覆盖
+    pub overlay: String,
+    /// 是否只列出过期的输出文件（已删除或改名的表之前输出的文件）而不删除
+    pub stale_dry_run: bool,
+    /// 是否将同一Excel（或声明了相同表名）的普通表和KV表Sheet合并为一个逻辑表，枚举表和本地化表保持各自的名称
+    pub merge_sheets: bool,
+    /// 是否按工作空间的目录结构输出到子目录，生成代码的命名空间包含目录名；SQLite和Protobuff不支持
+    pub mirror_folders: bool,
+    /// 字段命名规则，导出数据和生成代码共用
+    pub naming: ENamingType,
+    /// 字段别名，每行一个`字段名=别名`或`表名.字段名=别名`，优先于命名规则
+    pub field_aliases: String,
     // 是否生成脚本
     pub generate_script: bool,
     /// 脚本生成路径
@@ -43,13 +106,41 @@ impl Default for BuildSetting {
         Self {
             dev: EDevelopType::Cpp,
             display_name: String::new(),
+            id: String::new(),
             keyword: String::new(),
             target_type: ETargetType::Json,
+            csv_delimiter: ECsvDelimiterType::Comma,
+            csv_bom: false,
+            csv_line_ending: ELineEndingType::Lf,
+            csv_desc_row: true,
+            csv_type_row: true,
+            csv_array: ECsvArrayType::Text,
+            json_layout: EJsonLayoutType::Array,
+            json_minify: false,
+            json_schema: false,
             is_proto_2: false,
+            verify_protobuf: false,
+            proto_bundle: false,
+            msgpack_layout: EMsgPackLayoutType::Maps,
+            yaml_style: EYamlStyleType::Block,
+            yaml_comments: true,
+            xml_layout: EXmlLayoutType::Elements,
+            xml_schema: false,
+            sql_dialect: ESqlDialectType::MySQL,
+            lua_keyed: true,
+            lua_readonly: false,
+            data_template: PathBuf::new(),
+            data_extension: "txt".to_string(),
             is_custom: false,
             custom_template: PathBuf::new(),
             postprocessing: String::new(),
             target_path: PathBuf::new(),
+            overlay: String::new(),
+            stale_dry_run: false,
+            merge_sheets: false,
+            mirror_folders: false,
+            naming: ENamingType::Original,
+            field_aliases: String::new(),
             generate_script: false,
             script_path: PathBuf::new(),
         }
@@ -85,6 +176,11 @@ pub fn init() {
                 Ok(content) => {
                     let mut settings = APP_SETTINGS.write().unwrap();
                     *settings = content;
+                    if assign_setting_ids(&mut settings.build_settings)
+                        && let Err(e) = save_build_settings_to_file(&settings)
+                    {
+                        log::error!("Failed to save build settings: {}", e);
+                    }
                 }
                 Err(e) => {
                     log::error!("Failed to parse settings from JSON: {}", e);
@@ -193,18 +289,47 @@ pub fn add_build_setting(dev_type: EDevelopType) -> Option<usize> {
     let build_setting: BuildSetting = BuildSetting {
         dev: dev_type,
         display_name: dev_type.to_string().to_string(),
+        id: String::new(),
         keyword: dev_type.to_keyword().to_string(),
         target_type: ETargetType::Json,
+        csv_delimiter: ECsvDelimiterType::Comma,
+        csv_bom: false,
+        csv_line_ending: ELineEndingType::Lf,
+        csv_desc_row: true,
+        csv_type_row: true,
+        csv_array: ECsvArrayType::Text,
+        json_layout: EJsonLayoutType::Array,
+        json_minify: false,
+        json_schema: false,
         is_proto_2: false,
+        verify_protobuf: false,
+        proto_bundle: false,
+        msgpack_layout: EMsgPackLayoutType::Maps,
+        yaml_style: EYamlStyleType::Block,
+        yaml_comments: true,
+        xml_layout: EXmlLayoutType::Elements,
+        xml_schema: false,
+        sql_dialect: ESqlDialectType::MySQL,
+        lua_keyed: true,
+        lua_readonly: false,
+        data_template: PathBuf::new(),
+        data_extension: "txt".to_string(),
         is_custom: false,
         custom_template: PathBuf::new(),
         postprocessing: String::new(),
         target_path: utils::get_env_relative_path(&get_workspace()),
+        overlay: String::new(),
+        stale_dry_run: false,
+        merge_sheets: false,
+        mirror_folders: false,
+        naming: ENamingType::Original,
+        field_aliases: String::new(),
         generate_script: false,
         script_path: PathBuf::new(),
     };
     let mut settings = APP_SETTINGS.write().unwrap();
     settings.build_settings.push(build_setting);
+    assign_setting_ids(&mut settings.build_settings);
     if let Err(e) = save_build_settings_to_file(&*settings) {
         log::error!("Failed to save build settings: {}", e);
         None
@@ -297,6 +422,27 @@ pub fn update_build_setting(index: usize, setting: BuildSetting) -> io::Result<(
     }
 }
 
+/**
+ * 为没有唯一标识或标识重复的构建设置分配新的标识
+ * @param build_settings 构建设置列表
+ * @return 是否分配了新的标识
+ */
+fn assign_setting_ids(build_settings: &mut [BuildSetting]) -> bool {
+    let timestamp: i64 = chrono::Local::now()
+        .timestamp_nanos_opt()
+        .unwrap_or_default();
+    let mut ids: HashSet<String> = HashSet::new();
+    let mut changed: bool = false;
+    for (index, build_setting) in build_settings.iter_mut().enumerate() {
+        if build_setting.id.is_empty() || ids.contains(&build_setting.id) {
+            build_setting.id = format!("{:x}-{}", timestamp, index);
+            changed = true;
+        }
+        ids.insert(build_setting.id.clone());
+    }
+    changed
+}
+
 /**
  * 保存BuildSetting列表到JSON文件
  * @param settings AppSettings 当前设置项
@@ -307,6 +453,24 @@ fn save_build_settings_to_file(settings: &AppSettings) -> io::Result<()> {
     fs::write(path, json)
 }
 
+/**
+ * 根据文件路径确定所在的目录
+ * 相对于根目录（workspace）的目录，以`/`分隔，根目录下的文件为空字符串
+ * @param path 文件路径
+ * @return 目录
+ */
+pub fn determine_folder(path: &Path) -> String {
+    let workspace = get_workspace();
+    match path.parent().map(|parent| parent.strip_prefix(&workspace)) {
+        Some(Ok(relative_path)) => relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<String>>()
+            .join("/"),
+        _ => String::new(),
+    }
+}
+
 /**
  * 根据文件路径确定ESheetType
  * 以根目录为起点（workspace）下一级的文件夹名字来判断
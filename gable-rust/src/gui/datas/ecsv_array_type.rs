@@ -0,0 +1,25 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// csv单元格中数组和向量的写法
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum ECsvArrayType {
+    /// 与表格填写方式一致：`1;2;3`、`1;2|3;4`
+    Text = 0,
+    /// json：`[1,2,3]`、`[{"x":1,"y":2}]`
+    Json = 1,
+}
+
+impl ECsvArrayType {
+    pub fn iter() -> Iter<'static, ECsvArrayType> {
+        static VARIANTS: &[ECsvArrayType] = &[ECsvArrayType::Text, ECsvArrayType::Json];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ECsvArrayType::Text => "Text",
+            ECsvArrayType::Json => "Json",
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// csv分隔符
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum ECsvDelimiterType {
+    /// 逗号
+    Comma = 0,
+    /// 制表符
+    Tab = 1,
+    /// 分号
+    Semicolon = 2,
+}
+
+impl ECsvDelimiterType {
+    pub fn iter() -> Iter<'static, ECsvDelimiterType> {
+        static VARIANTS: &[ECsvDelimiterType] = &[
+            ECsvDelimiterType::Comma,
+            ECsvDelimiterType::Tab,
+            ECsvDelimiterType::Semicolon,
+        ];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ECsvDelimiterType::Comma => "Comma",
+            ECsvDelimiterType::Tab => "Tab",
+            ECsvDelimiterType::Semicolon => "Semicolon",
+        }
+    }
+    /// 分隔字符
+    pub fn as_char(&self) -> char {
+        match self {
+            ECsvDelimiterType::Comma => ',',
+            ECsvDelimiterType::Tab => '\t',
+            ECsvDelimiterType::Semicolon => ';',
+        }
+    }
+}
@@ -87,4 +87,36 @@ impl EDataType {
             _ => EDataType::Unknown,
         }
     }
+
+    /**
+     * 转换枚举为表格中的类型字符串，未知类型返回空字符串
+     */
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EDataType::Unknown => "",
+            EDataType::String => constant::DATA_TYPE_KEY_STRING,
+            EDataType::Int => constant::DATA_TYPE_KEY_INT,
+            EDataType::Long => constant::DATA_TYPE_KEY_LONG,
+            EDataType::Boolean => constant::DATA_TYPE_KEY_BOOLEAN,
+            EDataType::Float => constant::DATA_TYPE_KEY_FLOAT,
+            EDataType::Vector2 => constant::DATA_TYPE_KEY_VECTOR2,
+            EDataType::Vector3 => constant::DATA_TYPE_KEY_VECTOR3,
+            EDataType::Vector4 => constant::DATA_TYPE_KEY_VECTOR4,
+            EDataType::StringArr => constant::DATA_TYPE_KEY_STRING_ARR,
+            EDataType::IntArr => constant::DATA_TYPE_KEY_INT_ARR,
+            EDataType::LongArr => constant::DATA_TYPE_KEY_LONG_ARR,
+            EDataType::BooleanArr => constant::DATA_TYPE_KEY_BOOLEAN_ARR,
+            EDataType::FloatArr => constant::DATA_TYPE_KEY_FLOAT_ARR,
+            EDataType::Vector2Arr => constant::DATA_TYPE_KEY_VECTOR2_ARR,
+            EDataType::Vector3Arr => constant::DATA_TYPE_KEY_VECTOR3_ARR,
+            EDataType::Vector4Arr => constant::DATA_TYPE_KEY_VECTOR4_ARR,
+            EDataType::Percentage => constant::DATA_TYPE_KEY_PERCENTAGE,
+            EDataType::Permillage => constant::DATA_TYPE_KEY_PERMILLAGE,
+            EDataType::Permian => constant::DATA_TYPE_KEY_PERMIAN,
+            EDataType::Time => constant::DATA_TYPE_KEY_TIME,
+            EDataType::Date => constant::DATA_TYPE_KEY_DATE,
+            EDataType::Enum => constant::DATA_TYPE_KEY_ENUM,
+            EDataType::Loc => constant::DATA_TYPE_KEY_LOC,
+        }
+    }
 }
@@ -0,0 +1,32 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// json数据布局
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum EJsonLayoutType {
+    /// 行对象数组
+    Array = 0,
+    /// 以主键为键的对象，两个主键时嵌套
+    Keyed = 1,
+    /// 按列组织：`{"fields": [...], "rows": [[...]]}`
+    Columns = 2,
+}
+
+impl EJsonLayoutType {
+    pub fn iter() -> Iter<'static, EJsonLayoutType> {
+        static VARIANTS: &[EJsonLayoutType] = &[
+            EJsonLayoutType::Array,
+            EJsonLayoutType::Keyed,
+            EJsonLayoutType::Columns,
+        ];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EJsonLayoutType::Array => "Array",
+            EJsonLayoutType::Keyed => "Keyed",
+            EJsonLayoutType::Columns => "Columns",
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// 换行符
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum ELineEndingType {
+    /// `\n`
+    Lf = 0,
+    /// `\r\n`
+    CrLf = 1,
+}
+
+impl ELineEndingType {
+    pub fn iter() -> Iter<'static, ELineEndingType> {
+        static VARIANTS: &[ELineEndingType] = &[ELineEndingType::Lf, ELineEndingType::CrLf];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ELineEndingType::Lf => "LF",
+            ELineEndingType::CrLf => "CRLF",
+        }
+    }
+    /// 换行字符
+    pub fn as_line(&self) -> &'static str {
+        match self {
+            ELineEndingType::Lf => "\n",
+            ELineEndingType::CrLf => "\r\n",
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// MessagePack数据布局
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum EMsgPackLayoutType {
+    /// 每行导出为字段名到值的映射
+    Maps = 0,
+    /// 每行导出为按字段顺序排列的数组，不包含字段名
+    Arrays = 1,
+}
+
+impl EMsgPackLayoutType {
+    pub fn iter() -> Iter<'static, EMsgPackLayoutType> {
+        static VARIANTS: &[EMsgPackLayoutType] =
+            &[EMsgPackLayoutType::Maps, EMsgPackLayoutType::Arrays];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EMsgPackLayoutType::Maps => "Maps",
+            EMsgPackLayoutType::Arrays => "Arrays",
+        }
+    }
+}
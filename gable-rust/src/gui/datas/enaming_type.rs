@@ -0,0 +1,40 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// 字段命名规则
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum ENamingType {
+    /// 保持表格中的原始名称
+    Original = 0,
+    /// itemId
+    Camel = 1,
+    /// ItemId
+    Pascal = 2,
+    /// item_id
+    Snake = 3,
+    /// item-id
+    Kebab = 4,
+}
+
+impl ENamingType {
+    pub fn iter() -> Iter<'static, ENamingType> {
+        static VARIANTS: &[ENamingType] = &[
+            ENamingType::Original,
+            ENamingType::Camel,
+            ENamingType::Pascal,
+            ENamingType::Snake,
+            ENamingType::Kebab,
+        ];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ENamingType::Original => "Original",
+            ENamingType::Camel => "camelCase",
+            ENamingType::Pascal => "PascalCase",
+            ENamingType::Snake => "snake_case",
+            ENamingType::Kebab => "kebab-case",
+        }
+    }
+}
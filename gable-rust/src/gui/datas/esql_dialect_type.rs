@@ -0,0 +1,26 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// SQL方言
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum ESqlDialectType {
+    /// mysql
+    MySQL = 0,
+    /// postgresql
+    PostgreSQL = 1,
+}
+
+impl ESqlDialectType {
+    pub fn iter() -> Iter<'static, ESqlDialectType> {
+        static VARIANTS: &[ESqlDialectType] =
+            &[ESqlDialectType::MySQL, ESqlDialectType::PostgreSQL];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ESqlDialectType::MySQL => "MySQL",
+            ESqlDialectType::PostgreSQL => "PostgreSQL",
+        }
+    }
+}
@@ -15,6 +15,20 @@ pub enum ETargetType {
     Yaml = 3,
     /// protobuff
     Protobuff = 4,
+    /// messagepack
+    MessagePack = 5,
+    /// sqlite
+    SQLite = 6,
+    /// sql脚本
+    Sql = 7,
+    /// lua
+    Lua = 8,
+    /// toml
+    Toml = 9,
+    /// json5
+    Json5 = 10,
+    /// 自定义模板
+    Custom = 11,
 }
 
 impl ETargetType {
@@ -25,6 +39,13 @@ impl ETargetType {
             ETargetType::Xml,
             ETargetType::Yaml,
             ETargetType::Protobuff,
+            ETargetType::MessagePack,
+            ETargetType::SQLite,
+            ETargetType::Sql,
+            ETargetType::Lua,
+            ETargetType::Toml,
+            ETargetType::Json5,
+            ETargetType::Custom,
         ];
         VARIANTS.iter()
     }
@@ -35,6 +56,13 @@ impl ETargetType {
             ETargetType::Xml => "Xml",
             ETargetType::Yaml => "Yaml",
             ETargetType::Protobuff => "Protobuff",
+            ETargetType::MessagePack => "MessagePack",
+            ETargetType::SQLite => "SQLite",
+            ETargetType::Sql => "SQL",
+            ETargetType::Lua => "Lua",
+            ETargetType::Toml => "Toml",
+            ETargetType::Json5 => "Json5",
+            ETargetType::Custom => "Custom",
         }
     }
 }
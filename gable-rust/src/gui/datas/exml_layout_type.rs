@@ -0,0 +1,26 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// xml数据布局
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum EXmlLayoutType {
+    /// 每个字段为一个子节点：`<id>1</id>`
+    Elements = 0,
+    /// 标量字段为属性：`<item id="1"/>`，向量和数组仍为子节点
+    Attributes = 1,
+}
+
+impl EXmlLayoutType {
+    pub fn iter() -> Iter<'static, EXmlLayoutType> {
+        static VARIANTS: &[EXmlLayoutType] =
+            &[EXmlLayoutType::Elements, EXmlLayoutType::Attributes];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EXmlLayoutType::Elements => "Elements",
+            EXmlLayoutType::Attributes => "Attributes",
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::slice::Iter;
+
+use serde::{Deserialize, Serialize};
+
+/// YAML输出风格
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum EYamlStyleType {
+    /// 块风格，每个字段占一行
+    Block = 0,
+    /// 流风格，每行数据写在同一行`{ key: value }`
+    Flow = 1,
+}
+
+impl EYamlStyleType {
+    pub fn iter() -> Iter<'static, EYamlStyleType> {
+        static VARIANTS: &[EYamlStyleType] = &[EYamlStyleType::Block, EYamlStyleType::Flow];
+        VARIANTS.iter()
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EYamlStyleType::Block => "Block",
+            EYamlStyleType::Flow => "Flow",
+        }
+    }
+}
@@ -7,7 +7,13 @@ use lazy_static::lazy_static;
 use rayon::prelude::*;
 use std::sync::RwLock;
 use std::{
-    cmp::Ordering, collections::HashMap, fs, io::Error, path::Path, path::PathBuf, sync::Arc,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Error,
+    path::Path,
+    path::PathBuf,
+    sync::Arc,
 };
 
 lazy_static! {
@@ -62,7 +68,7 @@ fn has_eidtor_file(file_path: &PathBuf) -> (bool, Option<WatcherData>) {
  * @return 返回文件路径和对应GableData的映射
  */
 fn read_all_gable_files_parallel(
-    gable_files: &HashMap<String, Vec<(String, String)>>,
+    gable_files: &BTreeMap<String, Vec<(String, String)>>,
 ) -> HashMap<String, Option<GableData>> {
     // 收集所有文件路径
     let file_paths: Vec<String> = gable_files
@@ -94,7 +100,7 @@ fn build_tree_from_path(path: &Path) -> Vec<TreeItem> {
 
     // 收集目录项和文件项
     let mut directories: Vec<(PathBuf, String)> = Vec::new();
-    let mut gable_files: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let mut gable_files: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
 
     if let Ok(entries) = fs::read_dir(path) {
         for entry in entries.filter_map(|e: Result<fs::DirEntry, Error>| e.ok()) {
@@ -157,6 +163,7 @@ fn build_tree_from_path(path: &Path) -> Vec<TreeItem> {
                 let tree_data: Option<TreeData> = gable_content.map(|content| TreeData {
                     gable_type: sheet_type,
                     file_name: sheet_name.clone(),
+                    folder: setting::determine_folder(Path::new(&full_path)),
                     content,
                 });
 
@@ -253,7 +260,7 @@ fn build_item_from_path(path: &str, item_type: EItemType) -> Option<TreeItem> {
                     .unwrap_or("");
 
                 // 收集所有相关的sheet文件
-                let mut gable_files: HashMap<String, Vec<(String, String)>> = HashMap::new();
+                let mut gable_files: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
 
                 if let Ok(entries) = fs::read_dir(parent_path) {
                     for entry in entries.filter_map(|e: Result<fs::DirEntry, Error>| e.ok()) {
@@ -299,6 +306,7 @@ fn build_item_from_path(path: &str, item_type: EItemType) -> Option<TreeItem> {
                         let tree_data: Option<TreeData> = gable_content.map(|content| TreeData {
                             gable_type: sheet_type,
                             file_name: sheet_name.clone(),
+                            folder: setting::determine_folder(Path::new(full_path)),
                             content,
                         });
 
@@ -356,6 +364,7 @@ fn build_item_from_path(path: &str, item_type: EItemType) -> Option<TreeItem> {
                         let tree_data: Option<TreeData> = gable_content.map(|content| TreeData {
                             gable_type: sheet_type,
                             file_name: sheet_name.clone(),
+                            folder: setting::determine_folder(path_buf),
                             content,
                         });
 
@@ -534,6 +543,66 @@ where
     None
 }
 
+/**
+ * 获取枚举成员，跳过名称为空或值不是整数的行
+ * @param link_name 链接名称
+ * @return 枚举值和名称，枚举表不存在时返回None
+ */
+pub fn get_enum_members(link_name: &str) -> Option<Vec<(i64, String)>> {
+    get_enum_cells(link_name, |enum_datas| {
+        enum_datas
+            .cells
+            .iter()
+            .filter_map(|row_data| {
+                let name: &str = row_data
+                    .get(constant::TABLE_ENUM_COL_FIELD)
+                    .map(|cell| cell.value.as_str())
+                    .filter(|value| !value.is_empty())?;
+                let value: i64 = row_data
+                    .get(constant::TABLE_ENUM_COL_VALUE)?
+                    .value
+                    .trim()
+                    .parse()
+                    .ok()?;
+                Some((value, name.to_string()))
+            })
+            .collect()
+    })
+}
+
+/**
+ * 获取表所在的目录，不同目录下有同名的表时优先返回指定目录下的表
+ * @param table_name 表名（Sheet名称）
+ * @param prefer 优先查找的目录
+ * @return 目录，相对于工作空间，表不存在时返回None
+ */
+pub fn get_table_folder(table_name: &str, prefer: &str) -> Option<String> {
+    fn collect_folders<'a>(item: &'a TreeItem, table_name: &str, folders: &mut Vec<&'a str>) {
+        match item.data {
+            Some(ref tree_data)
+                if item.item_type == EItemType::Sheet && tree_data.file_name == table_name =>
+            {
+                folders.push(&tree_data.folder);
+            }
+            _ => {}
+        }
+        for child in &item.children {
+            collect_folders(child, table_name, folders);
+        }
+    }
+
+    let tree_items = TREE_ITEMS.read().unwrap();
+    let mut folders: Vec<&str> = Vec::new();
+    for root_item in tree_items.iter() {
+        collect_folders(root_item, table_name, &mut folders);
+    }
+    folders
+        .iter()
+        .find(|folder| **folder == prefer)
+        .or(folders.first())
+        .map(|folder| folder.to_string())
+}
+
 /**
  * 获取本地化数据
  * @param link_name 链接名称
@@ -638,6 +707,7 @@ pub fn add_new_item(new_path: &Path, new_item: EItemType) -> bool {
                     tree_data = Some(TreeData {
                         gable_type: sheet_type,
                         file_name: display_name.clone(),
+                        folder: setting::determine_folder(Path::new(&new_path)),
                         content: gable_data,
                     });
                 }
@@ -788,6 +858,7 @@ fn reload_gable(gable_file_paths: Option<Vec<String>>) -> bool {
                     item.data = new_data.map(|data: GableData| TreeData {
                         gable_type,
                         file_name: sheet_name,
+                        folder: setting::determine_folder(path),
                         content: data,
                     });
                     return true;
@@ -3,6 +3,7 @@ use chrono::format::DelayedFormat;
 use chrono::format::StrftimeItems;
 use log::LevelFilter;
 use once_cell::sync::OnceCell;
+use std::cell::RefCell;
 use std::fmt::Arguments;
 use std::fs;
 use std::fs::File;
@@ -16,6 +17,15 @@ use std::sync::Mutex;
 
 // 添加静态变量来存储全局LogRecord列表
 static GLOBAL_LOG_RECORDS: OnceCell<Arc<Mutex<Vec<LogRecord>>>> = OnceCell::new();
+// 全局日志实例，分组输出时需要直接写入
+static GLOBAL_LOGGER: OnceCell<LogTrace> = OnceCell::new();
+// 输出日志时持有的锁，保证同一组的日志连续输出
+static OUTPUT_LOCK: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    // 当前线程正在缓存的日志，为None时直接输出
+    static LOG_GROUP: RefCell<Option<Vec<LogRecord>>> = const { RefCell::new(None) };
+}
 
 #[derive(Debug, Clone)]
 pub struct LogRecord {
@@ -67,7 +77,8 @@ impl LogTrace {
         match LogTrace::new(log_dir_path) {
             Ok(logger) => {
                 // 设置全局实例
-                log::set_boxed_logger(Box::new(logger)).map(|()| log::set_max_level(level))
+                let logger: &'static LogTrace = GLOBAL_LOGGER.get_or_init(|| logger);
+                log::set_logger(logger).map(|()| log::set_max_level(level))
             }
             Err(_e) => {
                 // 确保全局日志记录列表已初始化
@@ -79,7 +90,8 @@ impl LogTrace {
                     level: level,
                 };
                 // 设置全局实例
-                log::set_boxed_logger(Box::new(logger)).map(|()| log::set_max_level(level))
+                let logger: &'static LogTrace = GLOBAL_LOGGER.get_or_init(|| logger);
+                log::set_logger(logger).map(|()| log::set_max_level(level))
             }
         }
     }
@@ -101,6 +113,106 @@ impl LogTrace {
             }
         }
     }
+
+    /**
+     * 收集日志：f执行期间当前线程的日志不输出，和f的返回值一起返回，之后可以按需要的顺序调用emit输出
+     * f发生panic时已收集的日志会直接输出
+     * @param f 需要收集日志的操作
+     * @return (f的返回值, 收集到的日志)
+     */
+    pub fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<LogRecord>) {
+        let outer: Option<Vec<LogRecord>> =
+            LOG_GROUP.with(|group| group.borrow_mut().replace(Vec::new()));
+        let guard: LogGroupGuard = LogGroupGuard { outer: Some(outer) };
+        let result: R = f();
+        (result, guard.finish())
+    }
+
+    /**
+     * 输出收集到的日志；当前线程正在分组时并入分组
+     * @param records 日志列表
+     */
+    pub fn emit(records: Vec<LogRecord>) {
+        let mut records: Option<Vec<LogRecord>> = Some(records);
+        LOG_GROUP.with(|group| {
+            if let Some(group) = group.borrow_mut().as_mut() {
+                group.extend(records.take().unwrap_or_default());
+            }
+        });
+        if let Some(records) = records
+            && let Some(logger) = GLOBAL_LOGGER.get()
+        {
+            let _guard = OUTPUT_LOCK.lock();
+            for record in records.iter() {
+                logger.write(record);
+            }
+        }
+    }
+
+    /**
+     * 输出一条日志：控制台、全局日志记录列表和日志文件
+     */
+    fn write(&self, record: &LogRecord) {
+        let log_message: String = format!(
+            "{} [{}] {} - {}\n",
+            record.timestamp, record.level, record.target, record.args
+        );
+
+        print!("{}", log_message);
+
+        // 更新全局日志记录列表
+        if let Some(global_records) = LogTrace::get_log_records() {
+            if let Ok(mut records) = global_records.lock() {
+                records.push(record.clone());
+
+                // 可以限制存储的日志数量，避免内存无限增长
+                if records.len() > 1000 {
+                    records.drain(0..100);
+                }
+            }
+        }
+
+        if let Ok(mut file_guard) = self.file.lock() {
+            if let Some(ref mut file) = *file_guard {
+                let _ = file.write_all(log_message.as_bytes());
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/**
+ * 收集日志期间恢复外层分组，f发生panic时输出已收集的日志
+ */
+struct LogGroupGuard {
+    /// 外层分组，恢复后为None
+    outer: Option<Option<Vec<LogRecord>>>,
+}
+
+impl LogGroupGuard {
+    /**
+     * 结束收集，恢复外层分组
+     * @return 收集到的日志
+     */
+    fn finish(mut self) -> Vec<LogRecord> {
+        self.restore()
+    }
+
+    fn restore(&mut self) -> Vec<LogRecord> {
+        let outer: Option<Vec<LogRecord>> = self.outer.take().unwrap_or_default();
+        LOG_GROUP
+            .with(|group| std::mem::replace(&mut *group.borrow_mut(), outer))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for LogGroupGuard {
+    fn drop(&mut self) {
+        if self.outer.is_some() {
+            let records: Vec<LogRecord> = self.restore();
+            LogTrace::emit(records);
+        }
+    }
 }
 
 impl log::Log for LogTrace {
@@ -112,37 +224,15 @@ impl log::Log for LogTrace {
         if self.enabled(record.metadata()) {
             let timestamp: DelayedFormat<StrftimeItems<'_>> =
                 Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-            let level: log::Level = record.level();
-            let target: &str = record.target();
             let args: &Arguments<'_> = record.args();
-
-            let log_message: String = format!("{} [{}] {} - {}\n", timestamp, level, target, args);
-
-            print!("{}", log_message);
-
-            // 更新全局日志记录列表
-            if let Some(global_records) = LogTrace::get_log_records() {
-                if let Ok(mut records) = global_records.lock() {
-                    records.push(LogRecord {
-                        timestamp: timestamp.to_string(),
-                        level,
-                        target: target.to_string(),
-                        args: args.to_string(),
-                    });
-
-                    // 可以限制存储的日志数量，避免内存无限增长
-                    if records.len() > 1000 {
-                        records.drain(0..100);
-                    }
-                }
-            }
-
-            if let Ok(mut file_guard) = self.file.lock() {
-                if let Some(ref mut file) = *file_guard {
-                    let _ = file.write_all(log_message.as_bytes());
-                    let _ = file.flush();
-                }
-            }
+            let log_record: LogRecord = LogRecord {
+                timestamp: timestamp.to_string(),
+                level: record.level(),
+                target: record.target().to_string(),
+                args: args.to_string(),
+            };
+            // 分组中的日志先缓存起来
+            LogTrace::emit(vec![log_record]);
         }
     }
 
@@ -0,0 +1,84 @@
+use crate::gui::datas::{
+    esheet_type::ESheetType,
+    tree_data::{FieldInfo, TreeData},
+};
+use serde_json::{Map, Value};
+
+/**
+ * 按关键字筛选后的表数据，每个表和关键字只构建一次，所有导出和代码生成共用
+ * 行的筛选、值的类型和字段顺序都以此为准，保证各个格式导出的数据一致
+ */
+pub struct TableData {
+    /// 表类型
+    pub gable_type: ESheetType,
+    /// 表名
+    pub file_name: String,
+    /// 所在目录，相对于工作空间，以`/`分隔，根目录为空字符串
+    pub folder: String,
+    /// 字段信息，枚举表的成员也在这里（field_index为枚举值）
+    pub field_info: FieldInfo,
+    /// 行数据，值已按字段类型转换；KV表只有一行，枚举表没有行数据
+    pub rows: Vec<Map<String, Value>>,
+}
+
+impl TableData {
+    /**
+     * 构建表数据
+     * @param tree_data 树数据
+     * @param keyword 关键字，用于筛选包含该关键字的数据
+     * @return 表数据，没有有效字段时返回None
+     */
+    pub fn new(tree_data: &TreeData, keyword: &str) -> Option<TableData> {
+        let field_info: FieldInfo = tree_data.to_fields(keyword)?;
+        let rows: Vec<Map<String, Value>> = if tree_data.gable_type == ESheetType::Enum {
+            Vec::new()
+        } else {
+            tree_data.to_values(keyword)
+        };
+        Some(TableData {
+            gable_type: tree_data.gable_type.clone(),
+            file_name: tree_data.file_name.clone(),
+            folder: tree_data.folder.clone(),
+            field_info,
+            rows,
+        })
+    }
+
+    /**
+     * 获取主键字段名
+     */
+    pub fn primary_keys(&self) -> Vec<&str> {
+        self.field_info
+            .main_fields
+            .iter()
+            .map(|field| field.field_name.as_str())
+            .collect()
+    }
+}
+
+/**
+ * 将值转换为表格中的文本格式，与单元格的填写方式一致
+ * 数组元素以`;`分隔，向量分量以`;`分隔，向量数组以`|`分隔，空值为空字符串
+ * @param value 值
+ * @return 文本
+ */
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::Array(items) => {
+            let separator: &str = if items.iter().any(|item| item.is_object()) {
+                "|"
+            } else {
+                ";"
+            };
+            let items: Vec<String> = items.iter().map(to_text).collect();
+            items.join(separator)
+        }
+        Value::Object(object) => {
+            let items: Vec<String> = object.values().map(to_text).collect();
+            items.join(";")
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
     },
 };
 use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 
 pub struct MainFieldItem {
     pub field_type: EDataType,
@@ -32,10 +33,207 @@ pub struct FieldInfo {
 pub struct TreeData {
     pub gable_type: ESheetType,
     pub file_name: String,
+    /// 所在目录，相对于工作空间，以`/`分隔，根目录为空字符串
+    pub folder: String,
     pub content: GableData,
 }
 
 impl TreeData {
+    /**
+     * 将多个Sheet合并为一个逻辑表
+     * 普通表和本地化表按字段名对齐列，所有Sheet的表头必须一致；KV表和枚举表直接拼接行
+     * @param table_name 逻辑表名
+     * @param parts 需要合并的Sheet数据
+     * @return 合并后的表数据，表结构或Sheet类型不一致时返回None
+     */
+    pub fn merge(table_name: &str, parts: &[&TreeData]) -> Option<TreeData> {
+        let first: &TreeData = parts.first()?;
+        let mut merged: TreeData = TreeData {
+            gable_type: first.gable_type.clone(),
+            file_name: table_name.to_string(),
+            folder: first.folder.clone(),
+            content: first.content.clone(),
+        };
+        let base_columns: BTreeMap<String, usize> = Self::field_columns(&first.content);
+        for part in parts.iter().skip(1) {
+            if part.gable_type != first.gable_type {
+                log::error!(
+                    "Sheet {} of table {} has type {:?}, expected {:?}",
+                    part.file_name,
+                    table_name,
+                    part.gable_type,
+                    first.gable_type
+                );
+                return None;
+            }
+            match first.gable_type {
+                ESheetType::Normal | ESheetType::Localize => {
+                    let part_columns: BTreeMap<String, usize> = Self::field_columns(&part.content);
+                    if !Self::verify_schema(table_name, first, &base_columns, part, &part_columns) {
+                        return None;
+                    }
+                    let max_col: usize = first.content.get_max_col();
+                    for row_data in part.content.cells.iter() {
+                        let mut new_row: Vec<CellData> =
+                            vec![CellData::new(String::new(), None, None); max_col];
+                        for (field_name, part_col) in part_columns.iter() {
+                            if let Some(cell) = row_data.get(*part_col) {
+                                new_row[base_columns[field_name]] = cell.clone();
+                            }
+                        }
+                        merged.content.cells.push(new_row);
+                    }
+                }
+                ESheetType::KV | ESheetType::Enum => {
+                    merged
+                        .content
+                        .cells
+                        .extend(part.content.cells.iter().cloned());
+                }
+            }
+        }
+        merged.check_duplicate_keys();
+        Some(merged)
+    }
+
+    /**
+     * 获取表头中字段名和列序号的映射，按字段名排序，保证校验日志的顺序稳定
+     * @param content 表数据
+     * @return 字段名 -> 列序号
+     */
+    fn field_columns(content: &GableData) -> BTreeMap<String, usize> {
+        let mut columns: BTreeMap<String, usize> = BTreeMap::new();
+        if let Some(field_row) = content.heads.get(constant::TABLE_NORMAL_ROW_FIELD) {
+            for (col_index, cell) in field_row.iter().enumerate() {
+                if cell.verify_lawful() {
+                    columns.entry(cell.value.clone()).or_insert(col_index);
+                }
+            }
+        }
+        columns
+    }
+
+    /**
+     * 校验Sheet的表头是否与逻辑表一致
+     * 字段集合相同，且每个字段除描述外的表头（类型、平台、关联信息）相同
+     * @return 是否一致
+     */
+    fn verify_schema(
+        table_name: &str,
+        base: &TreeData,
+        base_columns: &BTreeMap<String, usize>,
+        part: &TreeData,
+        part_columns: &BTreeMap<String, usize>,
+    ) -> bool {
+        let mut lawful: bool = true;
+        for field_name in base_columns.keys() {
+            if !part_columns.contains_key(field_name) {
+                log::error!(
+                    "Sheet {} of table {} is missing the field: {}",
+                    part.file_name,
+                    table_name,
+                    field_name
+                );
+                lawful = false;
+            }
+        }
+        for (field_name, part_col) in part_columns.iter() {
+            let base_col: usize = if let Some(base_col) = base_columns.get(field_name) {
+                *base_col
+            } else {
+                log::error!(
+                    "Sheet {} of table {} has an extra field: {}",
+                    part.file_name,
+                    table_name,
+                    field_name
+                );
+                lawful = false;
+                continue;
+            };
+            let row_count: usize = base.content.heads.len().max(part.content.heads.len());
+            for row_index in (constant::TABLE_NORMAL_ROW_FIELD + 1)..row_count {
+                let base_value: String = base
+                    .content
+                    .heads
+                    .get(row_index)
+                    .and_then(|row| row.get(base_col))
+                    .map(|cell| cell.value.clone())
+                    .unwrap_or_default();
+                let part_value: String = part
+                    .content
+                    .heads
+                    .get(row_index)
+                    .and_then(|row| row.get(*part_col))
+                    .map(|cell| cell.value.clone())
+                    .unwrap_or_default();
+                if base_value != part_value {
+                    log::error!(
+                        "Field {} of sheet {} does not match table {}: [{}] != [{}]",
+                        field_name,
+                        part.file_name,
+                        table_name,
+                        part_value,
+                        base_value
+                    );
+                    lawful = false;
+                }
+            }
+        }
+        lawful
+    }
+
+    /**
+     * 检查合并后的表是否存在重复的主键
+     */
+    fn check_duplicate_keys(&self) {
+        let key_cols: Vec<usize> = match self.gable_type {
+            ESheetType::Normal | ESheetType::Localize => self
+                .content
+                .heads
+                .get(constant::TABLE_NORMAL_ROW_FIELD)
+                .map(|row| {
+                    row.iter()
+                        .enumerate()
+                        .filter(|(_, cell)| cell.verify_lawful() && cell.value.contains("*"))
+                        .map(|(col_index, _)| col_index)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ESheetType::KV => vec![constant::TABLE_KV_COL_FIELD],
+            ESheetType::Enum => vec![constant::TABLE_ENUM_COL_FIELD],
+        };
+        if key_cols.is_empty() {
+            return;
+        }
+        // 按主键排序输出重复的主键
+        let mut keys: BTreeMap<Vec<String>, usize> = BTreeMap::new();
+        for row_data in self.content.cells.iter() {
+            let key: Vec<String> = key_cols
+                .iter()
+                .map(|col_index| {
+                    row_data
+                        .get(*col_index)
+                        .map(|cell| cell.value.clone())
+                        .unwrap_or_default()
+                })
+                .collect();
+            if key.iter().all(|v| v.is_empty()) {
+                continue;
+            }
+            *keys.entry(key).or_insert(0) += 1;
+        }
+        for (key, count) in keys.iter() {
+            if *count > 1 {
+                log::error!(
+                    "Duplicate key [{}] appears {} times in table {}",
+                    key.join(","),
+                    count,
+                    self.file_name
+                );
+            }
+        }
+    }
+
     /**
      * 将数据转换为值列表
      * @param keyword 关键字，用于筛选包含该关键字的数据
@@ -1,5 +1,12 @@
-use crate::gui::datas::{eitem_type::EItemType, tree_data::TreeData};
-use std::collections::HashMap;
+use crate::{
+    common::{constant, output, setting::BuildSetting, utils},
+    gui::datas::{eitem_type::EItemType, esheet_type::ESheetType, tree_data::TreeData},
+};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::Path,
+};
 
 #[derive(Debug, Clone)]
 pub struct TreeItem {
@@ -22,8 +29,8 @@ pub struct TreeItem {
 }
 
 impl TreeItem {
-    pub fn get_datas(&self) -> HashMap<String, &TreeData> {
-        let mut cache: HashMap<String, &TreeData> = HashMap::new();
+    pub fn get_datas(&self) -> BTreeMap<String, &TreeData> {
+        let mut cache: BTreeMap<String, &TreeData> = BTreeMap::new();
         if self.item_type == EItemType::Sheet {
             if let Some(data) = &self.data {
                 cache.insert(self.display_name.clone(), data);
@@ -31,10 +38,186 @@ impl TreeItem {
         }
 
         for item in &self.children {
-            let child_cache: HashMap<String, &TreeData> = item.get_datas();
+            let child_cache: BTreeMap<String, &TreeData> = item.get_datas();
             cache.extend(child_cache);
         }
 
         cache
     }
+
+    /**
+     * 获取按逻辑表名分组的Sheet数据
+     * 普通表和KV表的逻辑表名默认为所属Excel的名称，Sheet名称形如`表名#后缀`时使用声明的表名
+     * 枚举表和本地化表会被其他表的字段按名称引用，不合并，逻辑表名为Sheet自身的名称
+     * @return 逻辑表名和Sheet数据列表的映射
+     */
+    pub fn get_groups(&self) -> BTreeMap<String, Vec<&TreeData>> {
+        let mut groups: BTreeMap<String, Vec<&TreeData>> = BTreeMap::new();
+        match &self.data {
+            Some(data) if self.item_type == EItemType::Sheet => {
+                let table_name: String = match data.gable_type {
+                    ESheetType::Normal | ESheetType::KV => self.get_table_name(),
+                    ESheetType::Enum | ESheetType::Localize => data.file_name.clone(),
+                };
+                groups.entry(table_name).or_default().push(data);
+            }
+            _ => {}
+        }
+
+        for item in &self.children {
+            for (table_name, datas) in item.get_groups() {
+                groups.entry(table_name).or_default().extend(datas);
+            }
+        }
+
+        groups
+    }
+
+    /**
+     * 获取Sheet所属的逻辑表名
+     * @return 逻辑表名
+     */
+    fn get_table_name(&self) -> String {
+        if let Some(pos) = self.display_name.find(constant::TABLE_NAME_SEPARATOR) {
+            return self.display_name[..pos].to_string();
+        }
+        let file_name: String = Path::new(&self.fullpath)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match utils::parse_gable_filename(&file_name) {
+            Some((excel_name, _)) => excel_name,
+            None => self.display_name.clone(),
+        }
+    }
+
+    /**
+     * 获取所有Sheet的完整路径和数据
+     * @return Sheet的完整路径和数据列表
+     */
+    fn get_sheets(&self) -> Vec<(&str, &TreeData)> {
+        let mut sheets: Vec<(&str, &TreeData)> = Vec::new();
+        match &self.data {
+            Some(data) if self.item_type == EItemType::Sheet => {
+                sheets.push((&self.fullpath, data));
+            }
+            _ => {}
+        }
+        for item in &self.children {
+            sheets.extend(item.get_sheets());
+        }
+        sheets
+    }
+
+    /**
+     * 获取需要导出的表数据
+     * 合并Sheet时，选中的Sheet所属逻辑表的全部Sheet都会从根节点中收集并合并，同一逻辑表中Sheet类型不同时合并失败
+     * 不同目录下的Sheet输出名称相同时会互相覆盖，只导出部分表时也会检查全部Sheet，有重名时不导出
+     * @param roots 根节点列表
+     * @param items 选中的树节点列表
+     * @param build_setting 构建设置，决定是否合并Sheet以及是否按目录结构输出
+     * @return 输出名称和表数据的映射，按输出名称排序；有重名或Sheet合并失败时返回None
+     */
+    pub fn collect_tables<'a>(
+        roots: &'a [TreeItem],
+        items: &'a [TreeItem],
+        build_setting: &BuildSetting,
+    ) -> Option<BTreeMap<String, Cow<'a, TreeData>>> {
+        let mut tables: BTreeMap<String, Cow<'a, TreeData>> = BTreeMap::new();
+        if !build_setting.merge_sheets {
+            let mut sources: BTreeMap<String, &str> = BTreeMap::new();
+            let mut collided: bool = false;
+            for root in roots.iter() {
+                for (fullpath, data) in root.get_sheets() {
+                    let key: String =
+                        output::get_table_key(build_setting, &data.folder, &data.file_name);
+                    if let Some(other) = sources.get(&key) {
+                        log::error!(
+                            "Table name collision [{}]: {} is defined in both {} and {}",
+                            build_setting.display_name,
+                            key,
+                            other,
+                            fullpath
+                        );
+                        collided = true;
+                    } else {
+                        sources.insert(key, fullpath);
+                    }
+                }
+            }
+            if collided {
+                return None;
+            }
+            for item in items.iter() {
+                for (_, data) in item.get_sheets() {
+                    let key: String =
+                        output::get_table_key(build_setting, &data.folder, &data.file_name);
+                    tables.insert(key, Cow::Borrowed(data));
+                }
+            }
+            return Some(tables);
+        }
+
+        // 按目录结构输出时不同目录下的同名逻辑表分别合并，否则同名逻辑表的Sheet必须在同一目录
+        let mut selected: HashSet<String> = HashSet::new();
+        for item in items.iter() {
+            for (table_name, datas) in item.get_groups() {
+                for data in datas {
+                    selected.insert(output::get_table_key(
+                        build_setting,
+                        &data.folder,
+                        &table_name,
+                    ));
+                }
+            }
+        }
+        let mut groups: BTreeMap<String, (String, Vec<&TreeData>)> = BTreeMap::new();
+        for root in roots.iter() {
+            for (table_name, datas) in root.get_groups() {
+                for data in datas {
+                    let key: String =
+                        output::get_table_key(build_setting, &data.folder, &table_name);
+                    groups
+                        .entry(key)
+                        .or_insert_with(|| (table_name.clone(), Vec::new()))
+                        .1
+                        .push(data);
+                }
+            }
+        }
+        // 与不合并时相同，只导出部分表时也检查全部逻辑表
+        let mut collided: bool = false;
+        for (key, (_, datas)) in groups.iter() {
+            let folders: BTreeSet<&str> = datas.iter().map(|data| data.folder.as_str()).collect();
+            if folders.len() > 1 {
+                log::error!(
+                    "Table name collision [{}]: {} is defined in folders {:?}",
+                    build_setting.display_name,
+                    key,
+                    folders
+                );
+                collided = true;
+            }
+        }
+        if collided {
+            return None;
+        }
+        // 合并失败的表不能只跳过，否则构建会缺少该表的输出
+        let mut failed: bool = false;
+        for (key, (table_name, datas)) in groups {
+            if !selected.contains(&key) {
+                continue;
+            }
+            match TreeData::merge(&table_name, &datas) {
+                Some(data) => {
+                    tables.insert(key, Cow::Owned(data));
+                }
+                None => failed = true,
+            }
+        }
+        if failed {
+            return None;
+        }
+        Some(tables)
+    }
 }
@@ -305,14 +305,14 @@ impl GableApp {
                 ECommandType::ConvertTarget => {
                     if let Some(param) = command.param1 {
                         if let Some(setting) = setting::get_build_setting_with_name(&param) {
-                            convert::from_target(&setting);
+                            convert::from_target(&setting, false);
                         }
                     }
                 }
                 ECommandType::GenerateTarget => {
                     if let Some(param) = command.param1 {
                         if let Some(setting) = setting::get_build_setting_with_name(&param) {
-                            generate::from_target(&setting);
+                            generate::from_target(&setting, false);
                         }
                     }
                 }
@@ -5,7 +5,14 @@ use crate::{
         utils,
     },
     gui::{
-        datas::{edevelop_type::EDevelopType, etarget_type::ETargetType},
+        datas::{
+            ecsv_array_type::ECsvArrayType, ecsv_delimiter_type::ECsvDelimiterType,
+            edevelop_type::EDevelopType, ejson_layout_type::EJsonLayoutType,
+            eline_ending_type::ELineEndingType, emsgpack_layout_type::EMsgPackLayoutType,
+            enaming_type::ENamingType, esql_dialect_type::ESqlDialectType,
+            etarget_type::ETargetType, exml_layout_type::EXmlLayoutType,
+            eyaml_style_type::EYamlStyleType,
+        },
         gable_app::GableApp,
     },
 };
@@ -282,6 +289,408 @@ impl GableBuildSetting {
                     );
                 });
             });
+            // proto_bundle
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("proto_bundle")).truncate(),
+                    );
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            ui.add(Checkbox::new(&mut build_settings.proto_bundle, ""))
+                                .on_hover_text(locales::t("proto_bundle_hint"))
+                        },
+                    );
+                });
+            });
+            // verify_protobuf
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("verify_protobuf")).truncate(),
+                    );
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            ui.add(Checkbox::new(&mut build_settings.verify_protobuf, ""))
+                                .on_hover_text(locales::t("verify_protobuf_hint"))
+                        },
+                    );
+                });
+            });
+        }
+        if build_settings.target_type == ETargetType::CSV {
+            // csv_delimiter
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("csv_delimiter")).truncate(),
+                    );
+                    ComboBox::from_id_salt("build_settings.csv_delimiter")
+                        .selected_text(build_settings.csv_delimiter.as_str())
+                        .show_ui(ui, |ui| {
+                            for item in ECsvDelimiterType::iter() {
+                                ui.selectable_value(
+                                    &mut build_settings.csv_delimiter,
+                                    *item,
+                                    item.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(locales::t("csv_delimiter_hint"));
+                });
+            });
+            // csv_bom
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("csv_bom")).truncate());
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            ui.add(Checkbox::new(&mut build_settings.csv_bom, ""))
+                                .on_hover_text(locales::t("csv_bom_hint"))
+                        },
+                    );
+                });
+            });
+            // csv_line_ending
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("csv_line_ending")).truncate(),
+                    );
+                    ComboBox::from_id_salt("build_settings.csv_line_ending")
+                        .selected_text(build_settings.csv_line_ending.as_str())
+                        .show_ui(ui, |ui| {
+                            for item in ELineEndingType::iter() {
+                                ui.selectable_value(
+                                    &mut build_settings.csv_line_ending,
+                                    *item,
+                                    item.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(locales::t("csv_line_ending_hint"));
+                });
+            });
+            // csv_desc_row
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("csv_desc_row")).truncate(),
+                    );
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| ui.add(Checkbox::new(&mut build_settings.csv_desc_row, "")),
+                    );
+                });
+            });
+            // csv_type_row
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("csv_type_row")).truncate(),
+                    );
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            ui.add(Checkbox::new(&mut build_settings.csv_type_row, ""))
+                                .on_hover_text(locales::t("csv_type_row_hint"))
+                        },
+                    );
+                });
+            });
+            // csv_array
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("csv_array")).truncate());
+                    ComboBox::from_id_salt("build_settings.csv_array")
+                        .selected_text(build_settings.csv_array.as_str())
+                        .show_ui(ui, |ui| {
+                            for item in ECsvArrayType::iter() {
+                                ui.selectable_value(
+                                    &mut build_settings.csv_array,
+                                    *item,
+                                    item.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(locales::t("csv_array_hint"));
+                });
+            });
+        }
+        if build_settings.target_type == ETargetType::Json {
+            // json_layout
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("json_layout")).truncate());
+                    ComboBox::from_id_salt("build_settings.json_layout")
+                        .selected_text(build_settings.json_layout.as_str())
+                        .show_ui(ui, |ui| {
+                            for item in EJsonLayoutType::iter() {
+                                ui.selectable_value(
+                                    &mut build_settings.json_layout,
+                                    *item,
+                                    item.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(locales::t("json_layout_hint"));
+                });
+            });
+            // json_minify
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("json_minify")).truncate());
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| ui.add(Checkbox::new(&mut build_settings.json_minify, "")),
+                    );
+                });
+            });
+            // json_schema
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("json_schema")).truncate());
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            ui.add(Checkbox::new(&mut build_settings.json_schema, ""))
+                                .on_hover_text(locales::t("json_schema_hint"))
+                        },
+                    );
+                });
+            });
+        }
+        if build_settings.target_type == ETargetType::MessagePack {
+            // msgpack_layout
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("msgpack_layout")).truncate(),
+                    );
+                    ComboBox::from_id_salt("build_settings.msgpack_layout")
+                        .selected_text(build_settings.msgpack_layout.as_str())
+                        .show_ui(ui, |ui| {
+                            for item in EMsgPackLayoutType::iter() {
+                                ui.selectable_value(
+                                    &mut build_settings.msgpack_layout,
+                                    *item,
+                                    item.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(locales::t("msgpack_layout_hint"));
+                });
+            });
+        }
+        if build_settings.target_type == ETargetType::Yaml {
+            // yaml_style
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("yaml_style")).truncate());
+                    ComboBox::from_id_salt("build_settings.yaml_style")
+                        .selected_text(build_settings.yaml_style.as_str())
+                        .show_ui(ui, |ui| {
+                            for item in EYamlStyleType::iter() {
+                                ui.selectable_value(
+                                    &mut build_settings.yaml_style,
+                                    *item,
+                                    item.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(locales::t("yaml_style_hint"));
+                });
+            });
+            // yaml_comments
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("yaml_comments")).truncate(),
+                    );
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| ui.add(Checkbox::new(&mut build_settings.yaml_comments, "")),
+                    );
+                });
+            });
+        }
+        if build_settings.target_type == ETargetType::Xml {
+            // xml_layout
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("xml_layout")).truncate());
+                    ComboBox::from_id_salt("build_settings.xml_layout")
+                        .selected_text(build_settings.xml_layout.as_str())
+                        .show_ui(ui, |ui| {
+                            for item in EXmlLayoutType::iter() {
+                                ui.selectable_value(
+                                    &mut build_settings.xml_layout,
+                                    *item,
+                                    item.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(locales::t("xml_layout_hint"));
+                });
+            });
+            // xml_schema
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("xml_schema")).truncate());
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            ui.add(Checkbox::new(&mut build_settings.xml_schema, ""))
+                                .on_hover_text(locales::t("xml_schema_hint"))
+                        },
+                    );
+                });
+            });
+        }
+        if build_settings.target_type == ETargetType::Sql {
+            // sql_dialect
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("sql_dialect")).truncate());
+                    ComboBox::from_id_salt("build_settings.sql_dialect")
+                        .selected_text(build_settings.sql_dialect.as_str())
+                        .show_ui(ui, |ui| {
+                            for item in ESqlDialectType::iter() {
+                                ui.selectable_value(
+                                    &mut build_settings.sql_dialect,
+                                    *item,
+                                    item.as_str(),
+                                );
+                            }
+                        });
+                });
+            });
+        }
+        if build_settings.target_type == ETargetType::Lua {
+            // lua_keyed
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(title_size, Label::new(locales::t("lua_keyed")).truncate());
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            ui.add(Checkbox::new(&mut build_settings.lua_keyed, ""))
+                                .on_hover_text(locales::t("lua_keyed_hint"))
+                        },
+                    );
+                });
+            });
+            // lua_readonly
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("lua_readonly")).truncate(),
+                    );
+                    ui.allocate_ui_with_layout(
+                        content_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            ui.add(Checkbox::new(&mut build_settings.lua_readonly, ""))
+                                .on_hover_text(locales::t("lua_readonly_hint"))
+                        },
+                    );
+                });
+            });
+        }
+        if build_settings.target_type == ETargetType::Custom {
+            // data_template
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("data_template")).truncate(),
+                    );
+                    ui.allocate_ui_with_layout(
+                        second_size,
+                        Layout::left_to_right(Align::Min),
+                        |ui| {
+                            let absolute_path: PathBuf =
+                                utils::get_absolute_path(&build_settings.data_template);
+                            ui.add(
+                                Label::new(absolute_path.to_string_lossy().to_string()).truncate(),
+                            );
+                        },
+                    );
+                    if ui
+                        .add_sized(third_size, Button::new(locales::t("browse")))
+                        .clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .set_title(locales::t("select_data_template"))
+                            .pick_file()
+                    {
+                        let re_path: PathBuf = utils::get_env_relative_path(&path);
+                        build_settings.data_template = re_path;
+                    }
+                });
+            });
+            // data_extension
+            ui.horizontal(|ui| {
+                ui.group(|ui| {
+                    ui.set_min_size(item_size);
+                    ui.add_sized(
+                        title_size,
+                        Label::new(locales::t("data_extension")).truncate(),
+                    );
+                    ui.add_sized(
+                        content_size,
+                        TextEdit::singleline(&mut build_settings.data_extension),
+                    );
+                });
+            });
         }
 
         // target_path
@@ -308,6 +717,77 @@ impl GableBuildSetting {
                 }
             });
         });
+        // stale_dry_run
+        ui.horizontal(|ui| {
+            ui.group(|ui| {
+                ui.set_min_size(item_size);
+                ui.add_sized(
+                    title_size,
+                    Label::new(locales::t("stale_dry_run")).truncate(),
+                );
+                ui.allocate_ui_with_layout(content_size, Layout::left_to_right(Align::Min), |ui| {
+                    ui.add(Checkbox::new(&mut build_settings.stale_dry_run, ""))
+                        .on_hover_text(locales::t("stale_dry_run_hint"))
+                });
+            });
+        });
+        // overlay
+        ui.horizontal(|ui| {
+            ui.group(|ui| {
+                ui.set_min_size(item_size);
+                ui.add_sized(title_size, Label::new(locales::t("overlay")).truncate());
+                ui.add_sized(
+                    content_size,
+                    TextEdit::singleline(&mut build_settings.overlay)
+                        .hint_text(locales::t("overlay_hint")),
+                );
+            });
+        });
+        // merge_sheets
+        ui.horizontal(|ui| {
+            ui.group(|ui| {
+                ui.set_min_size(item_size);
+                ui.add_sized(
+                    title_size,
+                    Label::new(locales::t("merge_sheets")).truncate(),
+                );
+                ui.allocate_ui_with_layout(content_size, Layout::left_to_right(Align::Min), |ui| {
+                    ui.add(Checkbox::new(&mut build_settings.merge_sheets, ""))
+                        .on_hover_text(locales::t("merge_sheets_hint"))
+                });
+            });
+        });
+        // mirror_folders
+        ui.horizontal(|ui| {
+            ui.group(|ui| {
+                ui.set_min_size(item_size);
+                ui.add_sized(
+                    title_size,
+                    Label::new(locales::t("mirror_folders")).truncate(),
+                );
+                ui.allocate_ui_with_layout(content_size, Layout::left_to_right(Align::Min), |ui| {
+                    ui.add(Checkbox::new(&mut build_settings.mirror_folders, ""))
+                        .on_hover_text(locales::t("mirror_folders_hint"))
+                });
+            });
+        });
+        // naming
+        ui.horizontal(|ui| {
+            ui.group(|ui| {
+                ui.set_min_size(item_size);
+                ui.add_sized(
+                    title_size,
+                    Label::new(locales::t("naming_convention")).truncate(),
+                );
+                ComboBox::from_id_salt("build_settings.naming")
+                    .selected_text(build_settings.naming.as_str())
+                    .show_ui(ui, |ui| {
+                        for item in ENamingType::iter() {
+                            ui.selectable_value(&mut build_settings.naming, *item, item.as_str());
+                        }
+                    });
+            });
+        });
         // generate_script
         ui.horizontal(|ui| {
             ui.group(|ui| {
@@ -406,6 +886,23 @@ impl GableBuildSetting {
             }
         }
 
+        // field_aliases
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.add_sized(
+                    title_size,
+                    Label::new(locales::t("field_aliases")).truncate(),
+                );
+                ui.add_sized(content_size, Label::new(""));
+            });
+            ui.add(
+                TextEdit::multiline(&mut build_settings.field_aliases)
+                    .font(TextStyle::Monospace)
+                    .hint_text(locales::t("field_aliases_hint"))
+                    .desired_rows(4)
+                    .desired_width(f32::INFINITY),
+            );
+        });
         // postprocessing
         let theme = CodeTheme::from_memory(ui.ctx(), ui.style());
         let mut layouter = |ui: &Ui, buf: &dyn TextBuffer, wrap_width: f32| {
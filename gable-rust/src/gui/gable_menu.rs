@@ -90,8 +90,12 @@ impl GableMenu {
                         gable_popup::open_window(gable_popup::WINDOW_BUILD_SETTING);
                     }
                     if ui.button(locales::t("quick_build").as_str()).clicked() {
-                        convert::from_all();
-                        generate::from_all();
+                        convert::from_all(false);
+                        generate::from_all(false);
+                    }
+                    if ui.button(locales::t("full_build").as_str()).clicked() {
+                        convert::from_all(true);
+                        generate::from_all(true);
                     }
                 });
                 ui.menu_button(locales::t("select").as_str(), |ui| {
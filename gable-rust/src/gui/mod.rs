@@ -17,14 +17,24 @@ pub mod gable_popup;
 pub mod datas {
     pub mod action_command;
     pub mod cell_data;
+    pub mod ecsv_array_type;
+    pub mod ecsv_delimiter_type;
     pub mod edata_type;
     pub mod edevelop_type;
     pub mod eitem_type;
+    pub mod ejson_layout_type;
+    pub mod eline_ending_type;
+    pub mod emsgpack_layout_type;
+    pub mod enaming_type;
     pub mod esheet_type;
+    pub mod esql_dialect_type;
     pub mod etarget_type;
+    pub mod exml_layout_type;
+    pub mod eyaml_style_type;
     pub mod gable_data;
     pub mod gables;
     pub mod log;
+    pub mod table_data;
     pub mod tree_data;
     pub mod tree_item;
     pub mod watcher_data;
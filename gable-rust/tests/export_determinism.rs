@@ -0,0 +1,151 @@
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/**
+ * 导出示例工作目录，两次导出到不同的临时目录，所有输出文件必须逐字节相同
+ */
+#[test]
+fn export_samples_twice_is_byte_identical() {
+    let root: PathBuf =
+        std::env::temp_dir().join(format!("gable-export-determinism-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let first: BTreeMap<String, Vec<u8>> = export_samples(&root.join("first"));
+    let second: BTreeMap<String, Vec<u8>> = export_samples(&root.join("second"));
+    let _ = fs::remove_dir_all(&root);
+
+    assert!(!first.is_empty(), "the export produced no files");
+    assert_eq!(
+        first.keys().collect::<Vec<_>>(),
+        second.keys().collect::<Vec<_>>(),
+        "the two exports produced different files"
+    );
+    for (path, content) in first.iter() {
+        assert!(
+            second[path] == *content,
+            "{} differs between the two exports",
+            path
+        );
+    }
+}
+
+/**
+ * 将示例表复制到新的工作目录并导出数据和代码
+ * 程序从可执行文件所在目录读取设置，因此每次导出都使用一份复制的可执行文件
+ * @param run_dir 本次导出使用的目录
+ * @return 输出文件的相对路径和内容
+ */
+fn export_samples(run_dir: &Path) -> BTreeMap<String, Vec<u8>> {
+    let samples: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR")).join("../samples/configs/tables");
+    let workspace: PathBuf = run_dir.join("workspace");
+    let out_dir: PathBuf = run_dir.join("out");
+    copy_dir(&samples, &workspace);
+
+    let bin_dir: PathBuf = run_dir.join("bin");
+    fs::create_dir_all(bin_dir.join("__Datas")).unwrap();
+    let exe: &Path = Path::new(env!("CARGO_BIN_EXE_gable"));
+    let bin: PathBuf = bin_dir.join(exe.file_name().unwrap());
+    fs::copy(exe, &bin).unwrap();
+
+    let build_settings: Vec<Value> = [
+        ("Json", "Csharp"),
+        ("CSV", "TypeScript"),
+        ("Yaml", "Python"),
+        ("Toml", "Golang"),
+        ("Xml", "Java"),
+        ("Lua", "Lua"),
+        ("Sql", "Cpp"),
+        ("MessagePack", "Rust"),
+        ("Json5", "JavaScript"),
+        ("SQLite", "Cangjie"),
+        ("Protobuff", "Csharp"),
+    ]
+    .iter()
+    .map(|(target_type, dev)| {
+        let name: String = format!("{}-{}", target_type, dev);
+        json!({
+            "display_name": name,
+            "dev": dev,
+            "keyword": "c",
+            "target_type": target_type,
+            "target_path": out_dir.join(&name).join("data"),
+            "generate_script": true,
+            "script_path": out_dir.join(&name).join("script"),
+            "proto_bundle": true,
+        })
+    })
+    .collect();
+    let prefs: Value = json!({
+        "workspace": workspace,
+        "language": null,
+        "build_settings": build_settings,
+    });
+    fs::write(
+        bin_dir.join("__Datas").join("appPrefs.json"),
+        serde_json::to_string_pretty(&prefs).unwrap(),
+    )
+    .unwrap();
+
+    let output: Output = Command::new(&bin)
+        .args(["export", "--data", "--script", "--force"])
+        .current_dir(&workspace)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mut files: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    collect_files(&out_dir, &out_dir, &mut files);
+    // 字段编号表写入工作目录，也需要稳定
+    collect_files(&workspace.join("__Protos"), &workspace, &mut files);
+    files
+}
+
+/**
+ * 递归复制目录
+ */
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry: fs::DirEntry = entry.unwrap();
+        let target: PathBuf = to.join(entry.file_name());
+        if entry.path().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+}
+
+/**
+ * 递归读取目录下的所有文件
+ * @param dir 目录
+ * @param base 计算相对路径的根目录
+ * @param files 相对路径和文件内容
+ */
+fn collect_files(dir: &Path, base: &Path, files: &mut BTreeMap<String, Vec<u8>>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries {
+        let path: PathBuf = entry.unwrap().path();
+        if path.is_dir() {
+            collect_files(&path, base, files);
+        } else {
+            let relative: String = path
+                .strip_prefix(base)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/");
+            files.insert(relative, fs::read(&path).unwrap());
+        }
+    }
+}
@@ -20,7 +20,28 @@ public partial class TableManager
     {
         _{{CLASS_NAME | lower}}s = new Dictionary<{{info.main_fields[0].field_type}}, {{CLASS_NAME}}>();
         TextAsset asset = Resources.Load<TextAsset>("Tables/{{CLASS_NAME}}");
+        {%- if JSON_LAYOUT == "Keyed" %}
+        var array = LitJson.JsonMapper.ToObject<Dictionary<string, {{CLASS_NAME}}>>(asset.text).Values;
+        {%- elif JSON_LAYOUT == "Columns" %}
+        var array = new List<{{CLASS_NAME}}>();
+        LitJson.JsonData columns = LitJson.JsonMapper.ToObject(asset.text);
+        LitJson.JsonData fields = columns["fields"];
+        foreach (LitJson.JsonData row in columns["rows"])
+        {
+            LitJson.JsonData data = new LitJson.JsonData();
+            data.SetJsonType(LitJson.JsonType.Object);
+            for (int i = 0; i < fields.Count; i++)
+            {
+                if (row[i] != null)
+                {
+                    data[(string)fields[i]] = row[i];
+                }
+            }
+            array.Add(LitJson.JsonMapper.ToObject<{{CLASS_NAME}}>(data.ToJson()));
+        }
+        {%- else %}
         {{CLASS_NAME}}[] array = LitJson.JsonMapper.ToObject<{{CLASS_NAME}}[]>(asset.text);
+        {%- endif %}
         foreach (var item in array)
         {
             if (!_{{CLASS_NAME | lower}}s.ContainsKey(item.{{info.main_fields[0].field_name}}))
@@ -45,7 +66,32 @@ public partial class TableManager
     {
         _{{CLASS_NAME | lower}}s = new Dictionary<{{info.main_fields[0].field_type}}, Dictionary<{{info.main_fields[1].field_type}}, {{CLASS_NAME}}>>();
         TextAsset asset = Resources.Load<TextAsset>("Tables/{{CLASS_NAME}}");
+        {%- if JSON_LAYOUT == "Keyed" %}
+        var array = new List<{{CLASS_NAME}}>();
+        foreach (var group in LitJson.JsonMapper.ToObject<Dictionary<string, Dictionary<string, {{CLASS_NAME}}>>>(asset.text).Values)
+        {
+            array.AddRange(group.Values);
+        }
+        {%- elif JSON_LAYOUT == "Columns" %}
+        var array = new List<{{CLASS_NAME}}>();
+        LitJson.JsonData columns = LitJson.JsonMapper.ToObject(asset.text);
+        LitJson.JsonData fields = columns["fields"];
+        foreach (LitJson.JsonData row in columns["rows"])
+        {
+            LitJson.JsonData data = new LitJson.JsonData();
+            data.SetJsonType(LitJson.JsonType.Object);
+            for (int i = 0; i < fields.Count; i++)
+            {
+                if (row[i] != null)
+                {
+                    data[(string)fields[i]] = row[i];
+                }
+            }
+            array.Add(LitJson.JsonMapper.ToObject<{{CLASS_NAME}}>(data.ToJson()));
+        }
+        {%- else %}
         {{CLASS_NAME}}[] array = LitJson.JsonMapper.ToObject<{{CLASS_NAME}}[]>(asset.text);
+        {%- endif %}
         foreach (var item in array)
         {
             Dictionary<{{info.main_fields[1].field_type}}, {{CLASS_NAME}}> subItem;
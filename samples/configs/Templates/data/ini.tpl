@@ -0,0 +1,17 @@
+{%- if table_type != "Enum" -%}
+; {{ CLASS_NAME }}
+{%- if table_type == "KV" %}
+[{{ CLASS_NAME }}]
+{%- for field in fields %}
+{{ field.field_name }}={{ rows[0][field.field_name] | json_encode() }}
+{%- endfor %}
+{%- else %}
+{%- for row in rows %}
+
+[{{ CLASS_NAME }}.{% for key in main_fields %}{{ row[key.field_name] }}{% if not loop.last %}.{% endif %}{% endfor %}]
+{%- for field in fields %}
+{{ field.field_name }}={{ row[field.field_name] | json_encode() }}
+{%- endfor %}
+{%- endfor %}
+{%- endif %}
+{% endif -%}